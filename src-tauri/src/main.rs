@@ -321,6 +321,7 @@ async fn main() -> Result<()> {
         .invoke_handler(tauri::generate_handler![
             load_encounters_preview,
             load_encounter,
+            get_encounter_window_stats,
             get_encounter_count,
             open_most_recent_encounter,
             delete_encounter,
@@ -386,6 +387,8 @@ fn setup_db(resource_path: &Path) -> Result<(), rusqlite::Error> {
         migration_full_text_search(&tx)?;
     }
     stmt.finalize()?;
+
+    migration_encounter_logs(&tx)?;
     info!("finished setting up database");
     tx.commit()
 }
@@ -592,6 +595,28 @@ fn migration_full_text_search(tx: &Transaction) -> Result<(), rusqlite::Error> {
     )
 }
 
+fn migration_encounter_logs(tx: &Transaction) -> Result<(), rusqlite::Error> {
+    let mut stmt = tx.prepare("SELECT 1 FROM pragma_table_info(?) WHERE name=?")?;
+    if !stmt.exists(["encounter", "status_effect_log"])? {
+        tx.execute("ALTER TABLE encounter ADD COLUMN status_effect_log BLOB", [])?;
+    }
+    // only set for logs that track non-boss damage separately
+    if !stmt.exists(["encounter_preview", "boss_only_my_dps"])? {
+        tx.execute(
+            "ALTER TABLE encounter_preview ADD COLUMN boss_only_my_dps INTEGER",
+            [],
+        )?;
+    }
+    if !stmt.exists(["encounter_preview", "my_build"])? {
+        tx.execute("ALTER TABLE encounter_preview ADD COLUMN my_build TEXT", [])?;
+        tx.execute(
+            "ALTER TABLE encounter_preview ADD COLUMN my_party_supports INTEGER",
+            [],
+        )?;
+    }
+    stmt.finalize()
+}
+
 #[tauri::command]
 fn load_encounters_preview(
     window: tauri::Window,
@@ -643,11 +668,17 @@ fn load_encounters_preview(
         ""
     };
 
+    // logs that track non-boss damage separately can be shown in either view
     let boss_only_damage_filter = if filter.boss_only_damage {
-        "AND boss_only_damage = 1"
+        "AND (boss_only_damage = 1 OR boss_only_my_dps IS NOT NULL)"
     } else {
         ""
     };
+    let my_dps = if filter.boss_only_damage {
+        "coalesce(e.boss_only_my_dps, e.my_dps)"
+    } else {
+        "e.my_dps"
+    };
 
     let difficulty_filter = if !filter.difficulty.is_empty() {
         params.push(filter.difficulty);
@@ -656,8 +687,26 @@ fn load_encounters_preview(
         ""
     };
 
+    let build_filter = if !filter.build.is_empty() {
+        params.push(filter.build);
+        "AND e.my_build = ?"
+    } else {
+        ""
+    };
+
+    let party_supports_filter = if let Some(supports) = filter.party_supports {
+        params.push(supports.to_string());
+        "AND e.my_party_supports = ?"
+    } else {
+        ""
+    };
+
     let order = if filter.order == 1 { "ASC" } else { "DESC" };
-    let sort = format!("e.{}", filter.sort);
+    let sort = if filter.sort == "my_dps" {
+        my_dps.to_string()
+    } else {
+        format!("e.{}", filter.sort)
+    };
 
     let count_params = params.clone();
 
@@ -671,20 +720,25 @@ fn load_encounters_preview(
     e.favorite,
     e.cleared,
     e.local_player,
-    e.my_dps,
-    e.players
+    {},
+    e.players,
+    e.my_build,
+    e.my_party_supports
     FROM encounter_preview e {}
     WHERE e.duration > ? {}
-    {} {} {} {}
+    {} {} {} {} {} {}
     ORDER BY {} {}
     LIMIT ?
     OFFSET ?",
+        my_dps,
         join_clause,
         boss_filter,
         raid_clear_filter,
         favorite_filter,
         difficulty_filter,
         boss_only_damage_filter,
+        build_filter,
+        party_supports_filter,
         sort,
         order
     );
@@ -723,6 +777,8 @@ fn load_encounters_preview(
                 cleared: row.get(6)?,
                 local_player: row.get(7)?,
                 my_dps: row.get(8).unwrap_or(0),
+                my_build: row.get(10)?,
+                my_party_supports: row.get(11)?,
             })
         })
         .expect("could not query encounters");
@@ -734,14 +790,16 @@ fn load_encounters_preview(
         SELECT COUNT(*)
         FROM encounter_preview e {}
         WHERE duration > ? {}
-        {} {} {} {}
+        {} {} {} {} {} {}
         ",
         join_clause,
         boss_filter,
         raid_clear_filter,
         favorite_filter,
         difficulty_filter,
-        boss_only_damage_filter
+        boss_only_damage_filter,
+        build_filter,
+        party_supports_filter
     );
 
     let count: i32 = conn
@@ -755,7 +813,7 @@ fn load_encounters_preview(
 }
 
 #[tauri::command(async)]
-fn load_encounter(window: tauri::Window, id: String) -> Encounter {
+fn load_encounter(window: tauri::Window, id: String, boss_only: Option<bool>) -> Encounter {
     let path = window
         .app_handle()
         .path_resolver()
@@ -786,7 +844,8 @@ fn load_encounter(window: tauri::Window, id: String) -> Encounter {
        total_effective_shielding,
        applied_shield_buffs,
        boss_hp_log,
-       stagger_log
+       stagger_log,
+       status_effect_log
     FROM encounter JOIN encounter_preview USING (id)
     WHERE id = ?
     ",
@@ -829,6 +888,7 @@ fn load_encounter(window: tauri::Window, id: String) -> Encounter {
             let buffs: HashMap<u32, StatusEffect>;
             let debuffs: HashMap<u32, StatusEffect>;
             let applied_shield_buffs: HashMap<u32, StatusEffect>;
+            let mut status_effect_log: HashMap<String, StatusEffectLog> = HashMap::new();
             if compressed {
                 let raw_bytes: Vec<u8> = row.get(10).unwrap_or_default();
                 let mut decompress = GzDecoder::new(raw_bytes.as_slice());
@@ -873,6 +933,20 @@ fn load_encounter(window: tauri::Window, id: String) -> Encounter {
                 let stagger_str: String = row.get(21).unwrap_or_default();
                 stagger_stats = serde_json::from_str::<Option<StaggerStats>>(stagger_str.as_str())
                     .unwrap_or_default();
+
+                // older logs don't have this column
+                let raw_bytes: Vec<u8> = row.get(22).unwrap_or_default();
+                if !raw_bytes.is_empty() {
+                    let mut decompress = GzDecoder::new(raw_bytes.as_slice());
+                    let mut status_effect_string = String::new();
+                    decompress
+                        .read_to_string(&mut status_effect_string)
+                        .expect("could not decompress status_effect_log");
+                    status_effect_log = serde_json::from_str::<HashMap<String, StatusEffectLog>>(
+                        status_effect_string.as_str(),
+                    )
+                    .unwrap_or_default();
+                }
             } else {
                 let buff_str: String = row.get(10).unwrap_or_default();
                 buffs = serde_json::from_str::<HashMap<u32, StatusEffect>>(buff_str.as_str())
@@ -910,6 +984,7 @@ fn load_encounter(window: tauri::Window, id: String) -> Encounter {
                     applied_shield_buffs,
                     boss_hp_log,
                     stagger_stats,
+                    status_effect_log,
                     ..Default::default()
                 },
                 difficulty: row.get(13)?,
@@ -1008,9 +1083,69 @@ fn load_encounter(window: tauri::Window, id: String) -> Encounter {
 
     encounter.entities = entities;
 
+    // logs keep non-boss damage separately, so either view can be shown
+    let boss_only = boss_only.unwrap_or(encounter.boss_only_damage);
+    encounter.apply_damage_view(boss_only);
+
     encounter
 }
 
+// recompute player and skill stats for hits between start and end (ms from fight start)
+#[tauri::command(async)]
+fn get_encounter_window_stats(
+    window: tauri::Window,
+    id: String,
+    start: i64,
+    end: i64,
+) -> Result<HashMap<String, WindowStats>, String> {
+    if end <= start {
+        return Err(format!("window end {} is not after start {}", end, start));
+    }
+    let encounter = load_encounter(window, id, None);
+    // clamp to the fight
+    let start = start.max(0);
+    let end = end.min(encounter.duration);
+    if end <= start {
+        return Err(format!(
+            "window is outside the fight, which lasted {}ms",
+            encounter.duration
+        ));
+    }
+    let duration_seconds = (end - start) as f64 / 1000.0;
+
+    let mut window_stats: HashMap<String, WindowStats> = HashMap::new();
+    for entity in encounter
+        .entities
+        .values()
+        .filter(|e| e.entity_type == EntityType::PLAYER)
+    {
+        let mut stats = WindowStats::default();
+        for skill in entity.skills.values() {
+            let mut skill_stats = WindowStats::default();
+            for hit in skill
+                .skill_cast_log
+                .iter()
+                .flat_map(|cast| cast.hits.iter())
+                .filter(|hit| hit.in_view(encounter.boss_only_damage))
+                .filter(|hit| hit.timestamp >= start && hit.timestamp <= end)
+            {
+                stats.add_hit(hit);
+                skill_stats.add_hit(hit);
+            }
+            if skill_stats.hits > 0 {
+                skill_stats.finish(duration_seconds);
+                stats.skills.insert(skill.id, skill_stats);
+            }
+        }
+        if stats.hits > 0 {
+            stats.finish(duration_seconds);
+            window_stats.insert(entity.name.clone(), stats);
+        }
+    }
+
+    Ok(window_stats)
+}
+
 #[tauri::command]
 fn get_encounter_count(window: tauri::Window) -> i32 {
     let path = window
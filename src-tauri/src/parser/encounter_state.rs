@@ -7,6 +7,7 @@ use rsntp::SntpClient;
 use rusqlite::Connection;
 use std::cell::RefCell;
 use std::cmp::{max, Ordering};
+use std::collections::VecDeque;
 use std::default::Default;
 use std::rc::Rc;
 use std::sync::Arc;
@@ -24,6 +25,27 @@ use crate::parser::status_tracker::StatusEffectDetails;
 use crate::parser::utils::*;
 
 const RDPS_VALID_LIMIT: i64 = 25_000;
+// how far back the death recap looks
+const DEATH_RECAP_WINDOW_MS: i64 = 10_000;
+// a shield update this close to an incoming hit is counted as absorbing it
+const SHIELD_HIT_MATCH_MS: i64 = 500;
+// how long an instant heal is credited to its caster if the effect has no duration
+const HEAL_ATTRIBUTION_MS: i64 = 3_000;
+// effects and hits shortly after using a battle item belong to the same use
+const BATTLE_ITEM_MERGE_MS: i64 = 2_000;
+// boss hp history used for the kill time projection
+const KILL_PROJECTION_WINDOW_S: i32 = 30;
+const KILL_PROJECTION_SMOOTHING: f64 = 0.3;
+// slower than this the projection is too far out to mean anything
+const KILL_PROJECTION_MIN_HP_PER_SECOND: f64 = 1.0;
+// esther npcs and hits within this window belong to the same activation
+const ESTHER_MERGE_MS: i64 = 10_000;
+// stagger gained this soon after an esther hit is credited to the esther
+const ESTHER_STAGGER_WINDOW_MS: i64 = 1_000;
+// invincible hits on the boss within this gap are one invincible window
+const INVINCIBLE_MERGE_MS: i32 = 2_000;
+// a counter cast this soon before a counter notify was cast into the same counter window
+const COUNTER_WINDOW_MS: i64 = 3_000;
 
 #[derive(Debug)]
 pub struct EncounterState {
@@ -38,13 +60,35 @@ pub struct EncounterState {
     prev_stagger: i32,
 
     damage_log: HashMap<String, Vec<(i64, i64)>>,
+    // player name -> timestamps of every hit they landed, boss or not
+    activity_log: HashMap<String, Vec<i64>>,
     identity_log: HashMap<String, IdentityLog>,
     cast_log: HashMap<String, HashMap<u32, Vec<i32>>>,
+    incoming_hits: HashMap<String, VecDeque<IncomingHit>>,
+    status_effect_log: HashMap<String, StatusEffectLog>,
+    // player caster name -> target name -> intervals of the effects they applied
+    caster_status_effect_log: HashMap<String, HashMap<String, StatusEffectLog>>,
+    // (target name, instance id) -> (status effect id, applied at, expires at, player caster)
+    active_status_effects: HashMap<(String, u32), (u32, i64, Option<i64>, Option<String>)>,
+    // (target name, source name, status effect id) ->
+    // (instance id, amount left if known, applied at, active until)
+    heal_sources: HashMap<(String, String, u32), (u32, Option<i64>, i64, i64)>,
 
     boss_hp_log: HashMap<String, Vec<BossHpLog>>,
+    // (projected at, projected kill time) for the boss being projected
+    kill_projection_log: Vec<(i32, i32)>,
+    kill_projection_boss: String,
 
     stagger_log: Vec<(i32, f32)>,
     stagger_intervals: Vec<(i32, i32)>,
+    // object id -> last paralyzation point
+    paralyzation_points: HashMap<u64, i64>,
+    // (esther name, timestamp) of the last esther hit
+    last_esther_hit: Option<(String, i64)>,
+    // (player, skill id, relative timestamp) of casts with a counter tripod
+    counter_casts: Vec<(String, u32, i64)>,
+    // raid phase, advances on every boss kill transition
+    phase: u32,
 
     pub party_info: Vec<Vec<String>>,
     pub raid_difficulty: String,
@@ -56,6 +100,9 @@ pub struct EncounterState {
     ntp_fight_start: i64,
 
     pub rdps_valid: bool,
+    rdps_invalidation: RdpsInvalidation,
+    // rdps from locally tracked stats, doesn't need the stats api
+    pub local_rdps: bool,
 
     pub skill_tracker: SkillTracker,
 
@@ -74,11 +121,23 @@ impl EncounterState {
 
             prev_stagger: 0,
             damage_log: HashMap::new(),
+            activity_log: HashMap::new(),
             identity_log: HashMap::new(),
             boss_hp_log: HashMap::new(),
             cast_log: HashMap::new(),
+            incoming_hits: HashMap::new(),
+            status_effect_log: HashMap::new(),
+            caster_status_effect_log: HashMap::new(),
+            active_status_effects: HashMap::new(),
+            heal_sources: HashMap::new(),
+            kill_projection_log: Vec::new(),
+            kill_projection_boss: "".to_string(),
             stagger_log: Vec::new(),
             stagger_intervals: Vec::new(),
+            paralyzation_points: HashMap::new(),
+            last_esther_hit: None,
+            counter_casts: Vec::new(),
+            phase: 1,
 
             party_info: Vec::new(),
             raid_difficulty: "".to_string(),
@@ -91,6 +150,8 @@ impl EncounterState {
 
             // todo
             rdps_valid: false,
+            rdps_invalidation: RdpsInvalidation::default(),
+            local_rdps: false,
 
             skill_tracker: SkillTracker::new(),
 
@@ -111,16 +172,33 @@ impl EncounterState {
         self.raid_clear = false;
 
         self.damage_log = HashMap::new();
+        self.activity_log = HashMap::new();
         self.identity_log = HashMap::new();
         self.cast_log = HashMap::new();
+        self.incoming_hits = HashMap::new();
+        self.status_effect_log = HashMap::new();
+        self.caster_status_effect_log = HashMap::new();
+        // effects can outlive a reset, only drop the ones that already expired
+        let now = Utc::now().timestamp_millis();
+        self.active_status_effects
+            .retain(|_, (_, _, expire_at, _)| expire_at.map_or(true, |expire_at| expire_at > now));
+        self.heal_sources = HashMap::new();
         self.boss_hp_log = HashMap::new();
+        self.encounter.kill_projection = None;
+        self.kill_projection_log = Vec::new();
+        self.kill_projection_boss = "".to_string();
         self.stagger_log = Vec::new();
         self.stagger_intervals = Vec::new();
+        self.paralyzation_points = HashMap::new();
+        self.last_esther_hit = None;
+        self.counter_casts = Vec::new();
+        self.phase = 1;
         self.party_info = Vec::new();
 
         self.ntp_fight_start = 0;
 
         self.rdps_valid = false;
+        self.rdps_invalidation = RdpsInvalidation::default();
 
         self.skill_tracker = SkillTracker::new();
         
@@ -205,6 +283,7 @@ impl EncounterState {
             self.encounter.entities.insert(entity.name.clone(), entity);
         }
         self.encounter.local_player = entity.name;
+        self.active_status_effects = HashMap::new();
 
         // remove unrelated entities
         self.encounter.entities.retain(|_, e| {
@@ -224,6 +303,7 @@ impl EncounterState {
             .expect("failed to emit phase-transition");
 
         match phase_code {
+            1 => self.phase += 1,
             0 | 2 | 3 | 4 => {
                 if !self.encounter.current_boss_name.is_empty() {
                     let player_stats = stats_api.get_stats(self);
@@ -342,10 +422,27 @@ impl EncounterState {
             self.boss_dead_update = true;
         }
 
+        let now = Utc::now().timestamp_millis();
         entity.current_hp = 0;
         entity.is_dead = true;
         entity.damage_stats.deaths += 1;
-        entity.damage_stats.death_time = Utc::now().timestamp_millis();
+        entity.damage_stats.death_time = now;
+
+        // snapshot whatever hit the player right before they died
+        if entity.entity_type == EntityType::PLAYER && self.encounter.fight_start > 0 {
+            let timestamp = now - self.encounter.fight_start;
+            let hits = self
+                .incoming_hits
+                .remove(&entity.name)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|hit| hit.timestamp >= timestamp - DEATH_RECAP_WINDOW_MS)
+                .collect();
+            entity
+                .damage_stats
+                .death_log
+                .push(DeathRecap { timestamp, hits });
+        }
     }
 
     pub fn on_skill_start(
@@ -361,6 +458,25 @@ impl EncounterState {
             return (0, None);
         }
         let skill_name = get_skill_name(&skill_id);
+        // awakening variants (summons, later stages) belong to the base skill's cast
+        let awakening = if source_entity.entity_type == EntityType::PLAYER
+            && is_awakening_skill(skill_id)
+            && get_skill_family(skill_id) == skill_id
+        {
+            let (boss_name, boss_hp_percent, _) = self.get_boss_snapshot();
+            Some((boss_name, boss_hp_percent, self.phase))
+        } else {
+            None
+        };
+        if source_entity.entity_type == EntityType::PLAYER
+            && has_counter_tripod(skill_id, tripod_index)
+        {
+            let now = relative_timestamp as i64;
+            self.counter_casts
+                .retain(|(_, _, cast_at)| now - cast_at <= COUNTER_WINDOW_MS);
+            self.counter_casts
+                .push((source_entity.name.clone(), skill_id, now));
+        }
         let mut tripod_change = false;
         let entity = self
             .encounter
@@ -413,12 +529,15 @@ impl EncounterState {
             (timestamp - self.encounter.fight_start) as i32
         };
 
-        // if skills have different ids but the same name, we group them together
-        // dunno if this is right approach xd
+        // tripod/awakening variants and sub-skills are grouped under the first id seen
         let mut skill_id = skill_id;
+        let family_id = get_skill_family(skill_id);
+        let sub_skill = is_sub_skill(skill_id);
         let mut skill_summon_sources: Option<Vec<u32>> = None;
         if let Some(skill) = entity.skills.get_mut(&skill_id) {
-            skill.casts += 1;
+            if !sub_skill {
+                skill.casts += 1;
+            }
             tripod_change = check_tripod_index_change(skill.tripod_index, tripod_index)
                 || check_tripod_level_change(skill.tripod_level, tripod_level);
             skill.tripod_index = tripod_index;
@@ -427,9 +546,14 @@ impl EncounterState {
         } else if let Some(skill) = entity
             .skills
             .values_mut()
-            .find(|s| s.name == skill_name.clone())
+            .find(|s| get_skill_family(s.id) == family_id)
         {
-            skill.casts += 1;
+            if !sub_skill {
+                skill.casts += 1;
+            }
+            if !skill.variant_ids.contains(&skill_id) {
+                skill.variant_ids.push(skill_id);
+            }
             skill_id = skill.id;
             tripod_change = check_tripod_index_change(skill.tripod_index, tripod_index)
                 || check_tripod_level_change(skill.tripod_level, tripod_level);
@@ -454,7 +578,7 @@ impl EncounterState {
                     tripod_index,
                     tripod_level,
                     summon_sources: summons,
-                    casts: 1,
+                    casts: if sub_skill { 0 } else { 1 },
                     ..Default::default()
                 },
             );
@@ -463,14 +587,7 @@ impl EncounterState {
         if tripod_change {
             let mut tripod_data: Vec<TripodData> = vec![];
             if let (Some(tripod_index), Some(tripod_level)) = (tripod_index, tripod_level) {
-                let mut indexes = vec![tripod_index.first];
-                if tripod_index.second != 0 {
-                    indexes.push(tripod_index.second + 3);
-                }
-                // third row should never be set if second is not set
-                if tripod_index.third != 0 {
-                    indexes.push(tripod_index.third + 6);
-                }
+                let indexes = get_tripod_keys(&tripod_index);
                 let levels = [tripod_level.first, tripod_level.second, tripod_level.third];
                 if let Some(effect) = SKILL_FEATURE_DATA.get(&skill_id) {
                     for i in 0..indexes.len() {
@@ -496,16 +613,47 @@ impl EncounterState {
                 });
             }
         }
-        self.cast_log
-            .entry(entity.name.clone())
-            .or_default()
-            .entry(skill_id)
-            .or_default()
-            .push(relative_timestamp);
+        if let Some((boss_name, boss_hp_percent, phase)) = awakening {
+            entity.damage_stats.awakening_log.push(AwakeningUse {
+                timestamp: relative_timestamp as i64,
+                skill_id,
+                boss_name,
+                boss_hp_percent,
+                phase,
+                hyper_awakening: is_hyper_awakening_skill(skill_id),
+                ..Default::default()
+            });
+        }
+        if !sub_skill {
+            self.cast_log
+                .entry(entity.name.clone())
+                .or_default()
+                .entry(skill_id)
+                .or_default()
+                .push(relative_timestamp);
+        }
 
         (skill_id, skill_summon_sources)
     }
 
+    // the current boss if the hit includes it, otherwise the first target
+    pub fn get_primary_target(&self, target_ids: &[u64], entity_tracker: &EntityTracker) -> u64 {
+        target_ids
+            .iter()
+            .find(|target_id| {
+                entity_tracker
+                    .entities
+                    .get(*target_id)
+                    .map_or(false, |target| {
+                        target.entity_type == EntityType::BOSS
+                            && target.name == self.encounter.current_boss_name
+                    })
+            })
+            .or(target_ids.first())
+            .copied()
+            .unwrap_or_default()
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn on_damage(
         &mut self,
@@ -516,6 +664,7 @@ impl EncounterState {
         se_on_source: Vec<StatusEffectDetails>,
         se_on_target: Vec<StatusEffectDetails>,
         target_count: i32,
+        primary_target: bool,
         entity_tracker: &EntityTracker,
         player_stats: &Option<Cache<String, PlayerStats>>,
         timestamp: i64,
@@ -551,9 +700,6 @@ impl EncounterState {
             }
         };
 
-        if hit_flag == HitFlag::INVINCIBLE {
-            return;
-        }
         if hit_flag == HitFlag::DAMAGE_SHARE
             && damage_data.skill_id == 0
             && damage_data.skill_effect_id == 0
@@ -562,12 +708,32 @@ impl EncounterState {
         }
 
         let mut skill_effect_id = damage_data.skill_effect_id;
-        if proj_entity.entity_type == EntityType::PROJECTILE
+        if (proj_entity.entity_type == EntityType::PROJECTILE
+            || proj_entity.entity_type == EntityType::TRAP)
             && is_battle_item(skill_effect_id, "attack")
         {
             skill_effect_id = proj_entity.skill_effect_id;
         }
 
+        // invincible hits only count as wasted damage, they don't start or extend the fight
+        if hit_flag == HitFlag::INVINCIBLE {
+            if self.encounter.fight_start > 0 {
+                let skill_id = if damage_data.skill_id != 0 {
+                    damage_data.skill_id
+                } else {
+                    skill_effect_id
+                };
+                self.on_invincible_hit(
+                    dmg_src_entity,
+                    dmg_target_entity,
+                    skill_id,
+                    damage_data.damage,
+                    timestamp,
+                );
+            }
+            return;
+        }
+
         let mut source_entity = self
             .encounter
             .entities
@@ -587,21 +753,51 @@ impl EncounterState {
             })
             .to_owned();
         
+        // player damage to non-boss targets is always tracked separately so either view
+        // can be shown later, with boss only damage it is kept out of the boss totals
+        let non_boss_hit = target_entity.entity_type != EntityType::BOSS
+            && target_entity.entity_type != EntityType::PLAYER;
+        let non_boss_only = self.boss_only_damage && non_boss_hit;
+
         // if boss only damage is enabled
         // check if target is boss and not player
         // check if target is player and source is boss
         if self.boss_only_damage
-            && ((target_entity.entity_type != EntityType::BOSS
-                && target_entity.entity_type != EntityType::PLAYER)
+            && ((non_boss_hit
+                && (source_entity.entity_type != EntityType::PLAYER
+                    || self.encounter.fight_start == 0))
                 || (target_entity.entity_type == EntityType::PLAYER
                     && source_entity.entity_type != EntityType::BOSS))
         {
+            // adds and environmental damage still belong in the death recap
+            if target_entity.entity_type == EntityType::PLAYER && self.encounter.fight_start > 0 {
+                let skill_id = if damage_data.skill_id != 0 {
+                    damage_data.skill_id
+                } else {
+                    skill_effect_id
+                };
+                self.record_incoming_hit(
+                    &target_entity.name,
+                    IncomingHit {
+                        timestamp: timestamp - self.encounter.fight_start,
+                        source: source_entity.name.clone(),
+                        skill_id,
+                        skill_name: get_skill_name(&skill_id),
+                        damage: damage_data.damage,
+                        shield_absorbed: 0,
+                        hp_after: damage_data.target_current_hp,
+                    },
+                );
+            }
             return;
         }
 
         if self.encounter.fight_start == 0 {
             self.encounter.fight_start = timestamp;
             self.skill_tracker.fight_start = timestamp;
+            if self.local_rdps {
+                self.rdps_valid = true;
+            }
             if source_entity.entity_type == EntityType::PLAYER && damage_data.skill_id > 0 {
                 self.skill_tracker.new_cast(
                     source_entity.id,
@@ -623,7 +819,9 @@ impl EncounterState {
                 .expect("failed to emit raid-start");
         }
 
-        self.encounter.last_combat_packet = timestamp;
+        if !non_boss_only {
+            self.encounter.last_combat_packet = timestamp;
+        }
 
         source_entity.id = dmg_src_entity.id;
         
@@ -636,6 +834,7 @@ impl EncounterState {
         if target_entity.entity_type != EntityType::PLAYER && damage_data.target_current_hp < 0 {
             damage += damage_data.target_current_hp;
         }
+        let overkill = damage_data.damage - damage;
 
         let mut skill_id = if damage_data.skill_id != 0 {
             damage_data.skill_id
@@ -663,11 +862,15 @@ impl EncounterState {
         let relative_timestamp = (timestamp - self.encounter.fight_start) as i32;
 
         if !source_entity.skills.contains_key(&skill_id) {
+            let family_id = get_skill_family(skill_id);
             if let Some(skill) = source_entity
                 .skills
-                .values()
-                .find(|&s| s.name == *skill_name)
+                .values_mut()
+                .find(|s| get_skill_family(s.id) == family_id)
             {
+                if !skill.variant_ids.contains(&skill_id) {
+                    skill.variant_ids.push(skill_id);
+                }
                 skill_id = skill.id;
             } else {
                 let (skill_name, skill_icon, _) = get_skill_name_and_icon(
@@ -684,7 +887,11 @@ impl EncounterState {
                         name: skill_name,
                         icon: skill_icon,
                         summon_sources: skill_summon_sources.clone(),
-                        casts: 1,
+                        casts: if non_boss_only || is_sub_skill(skill_id) {
+                            0
+                        } else {
+                            1
+                        },
                         ..Default::default()
                     },
                 );
@@ -696,58 +903,242 @@ impl EncounterState {
         let mut skill_hit = SkillHit {
             damage,
             timestamp: relative_timestamp as i64,
+            crit: hit_flag == HitFlag::CRITICAL || hit_flag == HitFlag::DOT_CRITICAL,
+            back_attack: hit_option == HitOption::BACK_ATTACK,
+            front_attack: hit_option == HitOption::FRONTAL_ATTACK,
+            target_count,
+            non_boss: non_boss_hit,
             ..Default::default()
         };
 
-        skill.total_damage += damage;
-        if damage > skill.max_damage {
-            skill.max_damage = damage;
+        let attribute = get_damage_attribute_name(damage_data.damage_attribute);
+        let damage_type = get_damage_type_name(damage_data.damage_type);
+        let cleave = target_count > 1 && !primary_target;
+        let sub_source = if source_entity.entity_type == EntityType::PLAYER {
+            match proj_entity.entity_type {
+                EntityType::SUMMON => Some(("summon", proj_entity.npc_id)),
+                EntityType::PROJECTILE => Some(("projectile", skill_effect_id)),
+                EntityType::TRAP => Some(("trap", skill_effect_id)),
+                _ => None,
+            }
+        } else {
+            None
         }
-        skill.last_timestamp = timestamp;
+        .map(|(source_type, id)| {
+            let sub_source = SkillSubSource {
+                source_type: source_type.to_string(),
+                id,
+                name: if proj_entity.entity_type == EntityType::SUMMON {
+                    proj_entity.name.clone()
+                } else {
+                    SKILL_EFFECT_DATA
+                        .get(&id)
+                        .map(|effect| effect.comment.clone())
+                        .unwrap_or_default()
+                },
+                ..Default::default()
+            };
+            (format!("{}_{}", source_type, id), sub_source)
+        });
 
-        source_entity.damage_stats.damage_dealt += damage;
-        target_entity.damage_stats.damage_taken += damage;
+        // with boss only damage, non-boss hits only go into the non_boss stats and rdps
+        if !non_boss_only {
+            skill
+                .wasted_damage
+                .add_hit(&hit_flag, damage_data.damage, overkill);
+            source_entity.damage_stats.wasted_damage.add_hit(
+                &hit_flag,
+                damage_data.damage,
+                overkill,
+            );
 
-        source_entity.skill_stats.hits += 1;
-        skill.hits += 1;
+            skill.total_damage += damage;
+            if damage > skill.max_damage {
+                skill.max_damage = damage;
+            }
+            skill.last_timestamp = timestamp;
 
-        if hit_flag == HitFlag::CRITICAL || hit_flag == HitFlag::DOT_CRITICAL {
-            source_entity.skill_stats.crits += 1;
-            source_entity.damage_stats.crit_damage += damage;
-            skill.crits += 1;
-            skill.crit_damage += damage;
-            skill_hit.crit = true;
-        }
-        if hit_option == HitOption::BACK_ATTACK {
-            source_entity.skill_stats.back_attacks += 1;
-            source_entity.damage_stats.back_attack_damage += damage;
-            skill.back_attacks += 1;
-            skill.back_attack_damage += damage;
-            skill_hit.back_attack = true;
+            source_entity.damage_stats.damage_dealt += damage;
+            target_entity.damage_stats.damage_taken += damage;
+
+            if source_entity.entity_type == EntityType::ESTHER {
+                let esther = self.get_esther_use(&source_entity.name, None, timestamp);
+                esther.damage += damage;
+                esther.hits += 1;
+                self.last_esther_hit = Some((source_entity.name.clone(), timestamp));
+            }
+
+            for by_attribute in [
+                &mut skill.damage_by_attribute,
+                &mut source_entity.damage_stats.damage_by_attribute,
+            ] {
+                by_attribute
+                    .entry(attribute.to_string())
+                    .and_modify(|e| *e += damage)
+                    .or_insert(damage);
+            }
+            for by_type in [
+                &mut skill.damage_by_type,
+                &mut source_entity.damage_stats.damage_by_type,
+            ] {
+                by_type
+                    .entry(damage_type.to_string())
+                    .and_modify(|e| *e += damage)
+                    .or_insert(damage);
+            }
+
+            if cleave {
+                skill.cleave_damage += damage;
+            } else {
+                skill.primary_damage += damage;
+            }
+
+            if let Some((key, sub_source)) = sub_source.as_ref() {
+                skill
+                    .sub_sources
+                    .entry(key.clone())
+                    .or_insert_with(|| sub_source.clone())
+                    .add_hit(damage, skill_hit.crit);
+            }
+
+            if source_entity.entity_type == EntityType::PLAYER
+                && is_battle_item(skill_effect_id, "attack")
+            {
+                let relative_timestamp = relative_timestamp as i64;
+                let log = &mut source_entity.damage_stats.battle_item_log;
+                // the throw is usually logged from the projectile, otherwise log it here
+                let index = match log.iter().rposition(|item| {
+                    item.item_id == skill_effect_id
+                        && relative_timestamp - item.timestamp <= BATTLE_ITEM_MERGE_MS
+                }) {
+                    Some(index) => index,
+                    None => {
+                        log.push(BattleItemUse {
+                            timestamp: relative_timestamp,
+                            item_id: skill_effect_id,
+                            name: SKILL_EFFECT_DATA
+                                .get(&skill_effect_id)
+                                .and_then(|item| item.item_name.clone())
+                                .unwrap_or_default(),
+                            item_type: get_battle_item_type(skill_effect_id)
+                                .unwrap_or_default()
+                                .to_string(),
+                            ..Default::default()
+                        });
+                        log.len() - 1
+                    }
+                };
+                log[index].damage += damage;
+                log[index].hits += 1;
+            }
+
+            if source_entity.entity_type == EntityType::PLAYER && is_awakening_skill(skill_id) {
+                let log = &mut source_entity.damage_stats.awakening_log;
+                // cast before the fight started, use the first hit instead
+                if !log.iter().any(|awakening| awakening.skill_id == skill_id) {
+                    let (boss_name, boss_hp_percent, _) = self.get_boss_snapshot();
+                    log.push(AwakeningUse {
+                        timestamp: relative_timestamp as i64,
+                        skill_id,
+                        boss_name,
+                        boss_hp_percent,
+                        phase: self.phase,
+                        hyper_awakening: is_hyper_awakening_skill(skill_id),
+                        ..Default::default()
+                    });
+                }
+                if let Some(awakening) = log
+                    .iter_mut()
+                    .rev()
+                    .find(|awakening| awakening.skill_id == skill_id)
+                {
+                    awakening.damage += damage;
+                    awakening.hits += 1;
+                }
+            }
+
+            source_entity.skill_stats.hits += 1;
+            skill.hits += 1;
+
+            if skill_hit.crit {
+                source_entity.skill_stats.crits += 1;
+                source_entity.damage_stats.crit_damage += damage;
+                skill.crits += 1;
+                skill.crit_damage += damage;
+            }
+            if skill_hit.back_attack {
+                source_entity.skill_stats.back_attacks += 1;
+                source_entity.damage_stats.back_attack_damage += damage;
+                skill.back_attacks += 1;
+                skill.back_attack_damage += damage;
+            }
+            if skill_hit.front_attack {
+                source_entity.skill_stats.front_attacks += 1;
+                source_entity.damage_stats.front_attack_damage += damage;
+                skill.front_attacks += 1;
+                skill.front_attack_damage += damage;
+            }
         }
-        if hit_option == HitOption::FRONTAL_ATTACK {
-            source_entity.skill_stats.front_attacks += 1;
-            source_entity.damage_stats.front_attack_damage += damage;
-            skill.front_attacks += 1;
-            skill.front_attack_damage += damage;
-            skill_hit.front_attack = true;
+
+        // the non-boss share of the breakdowns, so either damage view can be shown
+        if non_boss_hit && source_entity.entity_type == EntityType::PLAYER {
+            for non_boss in [
+                &mut skill.non_boss,
+                &mut source_entity.damage_stats.non_boss,
+            ] {
+                non_boss
+                    .wasted_damage
+                    .add_hit(&hit_flag, damage_data.damage, overkill);
+                *non_boss
+                    .damage_by_attribute
+                    .entry(attribute.to_string())
+                    .or_default() += damage;
+                *non_boss
+                    .damage_by_type
+                    .entry(damage_type.to_string())
+                    .or_default() += damage;
+            }
+            let non_boss = &mut skill.non_boss;
+            if cleave {
+                non_boss.cleave_damage += damage;
+            } else {
+                non_boss.primary_damage += damage;
+            }
+            non_boss.max_damage = max(non_boss.max_damage, damage);
+            if let Some((key, sub_source)) = sub_source {
+                non_boss
+                    .sub_sources
+                    .entry(key)
+                    .or_insert(sub_source)
+                    .add_hit(damage, skill_hit.crit);
+            }
+        } else if source_entity.entity_type == EntityType::PLAYER {
+            let non_boss = &mut skill.non_boss;
+            non_boss.boss_max_damage = max(non_boss.boss_max_damage, damage);
+            if let Some((key, _)) = sub_source {
+                let max_damage = non_boss.sub_source_boss_max_damage.entry(key).or_default();
+                *max_damage = max(*max_damage, damage);
+            }
         }
 
         if source_entity.entity_type == EntityType::PLAYER {
-            self.encounter.encounter_damage_stats.total_damage_dealt += damage;
-            self.encounter.encounter_damage_stats.top_damage_dealt = max(
-                self.encounter.encounter_damage_stats.top_damage_dealt,
-                source_entity.damage_stats.damage_dealt,
-            );
-
-            self.damage_log
+            self.activity_log
                 .entry(source_entity.name.clone())
                 .or_default()
-                .push((timestamp, damage));
+                .push(timestamp);
+            if !non_boss_only {
+                self.encounter.encounter_damage_stats.total_damage_dealt += damage;
+                self.encounter.encounter_damage_stats.top_damage_dealt = max(
+                    self.encounter.encounter_damage_stats.top_damage_dealt,
+                    source_entity.damage_stats.damage_dealt,
+                );
+
+                self.damage_log
+                    .entry(source_entity.name.clone())
+                    .or_default()
+                    .push((timestamp, damage));
+            }
 
-            let mut is_buffed_by_support = false;
-            let mut is_buffed_by_identity = false;
-            let mut is_debuffed_by_support = false;
             let se_on_source_ids = se_on_source
                 .iter()
                 .map(|se| map_status_effect(se, &mut self.custom_id_map))
@@ -785,26 +1176,6 @@ impl EncounterState {
                             .insert(*buff_id);
                     }
                 }
-                if !is_buffed_by_support {
-                    if let Some(buff) = self.encounter.encounter_damage_stats.buffs.get(buff_id) {
-                        if let Some(skill) = buff.source.skill.as_ref() {
-                            is_buffed_by_support = is_support_class_id(skill.class_id)
-                                && buff.buff_type & StatusEffectBuffTypeFlags::DMG.bits() != 0
-                                && buff.target == StatusEffectTarget::PARTY
-                                && buff.buff_category == "classskill";
-                        }
-                    }
-                }
-                if !is_buffed_by_identity {
-                    if let Some(buff) = self.encounter.encounter_damage_stats.buffs.get(buff_id) {
-                        if let Some(skill) = buff.source.skill.as_ref() {
-                            is_buffed_by_identity = is_support_class_id(skill.class_id)
-                                && buff.buff_type & StatusEffectBuffTypeFlags::DMG.bits() != 0
-                                && buff.target == StatusEffectTarget::PARTY
-                                && buff.buff_category == "identity";
-                        }
-                    }
-                }
             }
             let se_on_target_ids = se_on_target
                 .iter()
@@ -844,60 +1215,65 @@ impl EncounterState {
                             .insert(*debuff_id);
                     }
                 }
-                if !is_debuffed_by_support {
-                    if let Some(debuff) =
-                        self.encounter.encounter_damage_stats.debuffs.get(debuff_id)
-                    {
-                        if let Some(skill) = debuff.source.skill.as_ref() {
-                            is_debuffed_by_support = is_support_class_id(skill.class_id)
-                                && debuff.buff_type & StatusEffectBuffTypeFlags::DMG.bits() != 0
-                                && debuff.target == StatusEffectTarget::PARTY;
-                        }
-                    }
-                }
             }
 
-            if is_buffed_by_support {
-                skill.buffed_by_support += damage;
-                source_entity.damage_stats.buffed_by_support += damage;
-            }
-            if is_buffed_by_identity {
-                skill.buffed_by_identity += damage;
-                source_entity.damage_stats.buffed_by_identity += damage;
-            }
-            if is_debuffed_by_support {
-                skill.debuffed_by_support += damage;
-                source_entity.damage_stats.debuffed_by_support += damage;
-            }
+            let (is_buffed_by_support, is_buffed_by_identity, is_debuffed_by_support) =
+                self.get_support_buff_flags(&se_on_source_ids, &se_on_target_ids);
+            if !non_boss_only {
+                if is_buffed_by_support {
+                    skill.buffed_by_support += damage;
+                    source_entity.damage_stats.buffed_by_support += damage;
+                }
+                if is_buffed_by_identity {
+                    skill.buffed_by_identity += damage;
+                    source_entity.damage_stats.buffed_by_identity += damage;
+                }
+                if is_debuffed_by_support {
+                    skill.debuffed_by_support += damage;
+                    source_entity.damage_stats.debuffed_by_support += damage;
+                }
 
-            for buff_id in se_on_source_ids.iter() {
-                skill
-                    .buffed_by
-                    .entry(*buff_id)
-                    .and_modify(|e| *e += damage)
-                    .or_insert(damage);
-                source_entity
-                    .damage_stats
-                    .buffed_by
-                    .entry(*buff_id)
-                    .and_modify(|e| *e += damage)
-                    .or_insert(damage);
+                for buff_id in se_on_source_ids.iter() {
+                    skill
+                        .buffed_by
+                        .entry(*buff_id)
+                        .and_modify(|e| *e += damage)
+                        .or_insert(damage);
+                    source_entity
+                        .damage_stats
+                        .buffed_by
+                        .entry(*buff_id)
+                        .and_modify(|e| *e += damage)
+                        .or_insert(damage);
+                }
+                for debuff_id in se_on_target_ids.iter() {
+                    skill
+                        .debuffed_by
+                        .entry(*debuff_id)
+                        .and_modify(|e| *e += damage)
+                        .or_insert(damage);
+                    source_entity
+                        .damage_stats
+                        .debuffed_by
+                        .entry(*debuff_id)
+                        .and_modify(|e| *e += damage)
+                        .or_insert(damage);
+                }
             }
-            for debuff_id in se_on_target_ids.iter() {
-                skill
-                    .debuffed_by
-                    .entry(*debuff_id)
-                    .and_modify(|e| *e += damage)
-                    .or_insert(damage);
+            skill_hit.buffed_by = se_on_source_ids;
+            skill_hit.debuffed_by = se_on_target_ids;
+            if non_boss_hit {
+                let support_flags = (
+                    is_buffed_by_support,
+                    is_buffed_by_identity,
+                    is_debuffed_by_support,
+                );
+                skill.non_boss.add_hit(&skill_hit, support_flags);
                 source_entity
                     .damage_stats
-                    .debuffed_by
-                    .entry(*debuff_id)
-                    .and_modify(|e| *e += damage)
-                    .or_insert(damage);
+                    .non_boss
+                    .add_hit(&skill_hit, support_flags);
             }
-            skill_hit.buffed_by = se_on_source_ids;
-            skill_hit.debuffed_by = se_on_target_ids;
 
             // todo
             if let (true, Some(player_stats)) =
@@ -934,6 +1310,16 @@ impl EncounterState {
                                             - self.encounter.fight_start
                                     );
                                     self.rdps_valid = false;
+                                    if self.rdps_invalidation.add(
+                                        RdpsInvalidReason::MissingStats,
+                                        Some(&caster_entity.name),
+                                        self.encounter.last_combat_packet
+                                            - self.encounter.fight_start,
+                                    ) {
+                                        self.window
+                                            .emit("rdps-invalid", &self.rdps_invalidation)
+                                            .expect("failed to emit rdps-invalid");
+                                    }
                                     if !self.rdps_valid {
                                         self.window
                                             .emit("rdps", "invalid_stats")
@@ -977,6 +1363,7 @@ impl EncounterState {
                                         (val as f64 / 10000.0) * status_effect.stack_count as f64;
                                     rdps_data.multi_dmg.values.push(RdpsBuffData {
                                         caster: caster_encounter_entity.name.clone(),
+                                        buff_id: status_effect.status_effect_id,
                                         rate,
                                     });
                                     rdps_data.multi_dmg.sum_rate += rate;
@@ -996,9 +1383,14 @@ impl EncounterState {
                                     (val as f64 / 10000.0) * status_effect.stack_count as f64;
                                 let caster_base_atk_power = caster_stats.stats.atk_power;
                                 let target_base_atk_power = dmg_src_stats.stats.atk_power;
+                                // missing attack power would scale the rate to inf or nan
+                                if caster_base_atk_power == 0 || target_base_atk_power == 0 {
+                                    continue;
+                                }
                                 rate *= caster_base_atk_power as f64 / target_base_atk_power as f64;
                                 rdps_data.atk_pow_amplify.push(RdpsBuffData {
                                     caster: caster_encounter_entity.name.clone(),
+                                    buff_id: status_effect.status_effect_id,
                                     rate,
                                 });
                             }
@@ -1015,6 +1407,7 @@ impl EncounterState {
                                     {
                                         rdps_data.atk_pow_sub_rate_2.values.push(RdpsBuffData {
                                             caster: caster_encounter_entity.name.clone(),
+                                            buff_id: status_effect.status_effect_id,
                                             rate,
                                         });
                                         rdps_data.atk_pow_sub_rate_2.sum_rate += rate;
@@ -1029,6 +1422,7 @@ impl EncounterState {
                                     {
                                         rdps_data.atk_pow_sub_rate_1.values.push(RdpsBuffData {
                                             caster: caster_encounter_entity.name.clone(),
+                                            buff_id: status_effect.status_effect_id,
                                             rate,
                                         });
                                         rdps_data.atk_pow_sub_rate_1.sum_rate += rate;
@@ -1040,6 +1434,7 @@ impl EncounterState {
                                     {
                                         rdps_data.skill_dmg_rate.values.push(RdpsBuffData {
                                             caster: caster_encounter_entity.name.clone(),
+                                            buff_id: status_effect.status_effect_id,
                                             rate,
                                         });
                                         rdps_data.skill_dmg_rate.sum_rate += rate;
@@ -1055,6 +1450,7 @@ impl EncounterState {
                                 {
                                     rdps_data.crit.values.push(RdpsBuffData {
                                         caster: caster_encounter_entity.name.clone(),
+                                        buff_id: status_effect.status_effect_id,
                                         rate,
                                     });
                                     rdps_data.crit.sum_rate += rate;
@@ -1076,6 +1472,7 @@ impl EncounterState {
                                     }
                                     rdps_data.multi_dmg.values.push(RdpsBuffData {
                                         caster: caster_encounter_entity.name.clone(),
+                                        buff_id: status_effect.status_effect_id,
                                         rate,
                                     });
                                     rdps_data.multi_dmg.sum_rate += rate;
@@ -1143,6 +1540,7 @@ impl EncounterState {
                                 {
                                     rdps_data.crit.values.push(RdpsBuffData {
                                         caster: caster_encounter_entity.name.clone(),
+                                        buff_id: status_effect.status_effect_id,
                                         rate,
                                     });
                                     rdps_data.crit.sum_rate += rate;
@@ -1166,6 +1564,7 @@ impl EncounterState {
                                         * 0.5;
                                     rdps_data.multi_dmg.values.push(RdpsBuffData {
                                         caster: caster_encounter_entity.name.clone(),
+                                        buff_id: status_effect.status_effect_id,
                                         rate,
                                     });
                                     rdps_data.multi_dmg.sum_rate += rate;
@@ -1178,6 +1577,7 @@ impl EncounterState {
                                         (val as f64 / 10000.0) * status_effect.stack_count as f64;
                                     rdps_data.multi_dmg.values.push(RdpsBuffData {
                                         caster: caster_encounter_entity.name.clone(),
+                                        buff_id: status_effect.status_effect_id,
                                         rate,
                                     });
                                     rdps_data.multi_dmg.sum_rate += rate;
@@ -1191,6 +1591,7 @@ impl EncounterState {
                                             * status_effect.stack_count as f64;
                                         rdps_data.multi_dmg.values.push(RdpsBuffData {
                                             caster: caster_encounter_entity.name.clone(),
+                                            buff_id: status_effect.status_effect_id,
                                             rate,
                                         });
                                         rdps_data.multi_dmg.sum_rate += rate;
@@ -1206,6 +1607,7 @@ impl EncounterState {
                                         * 0.5;
                                     rdps_data.multi_dmg.values.push(RdpsBuffData {
                                         caster: caster_encounter_entity.name.clone(),
+                                        buff_id: status_effect.status_effect_id,
                                         rate,
                                     });
                                     rdps_data.multi_dmg.sum_rate += rate;
@@ -1218,6 +1620,7 @@ impl EncounterState {
                                         val as f64 / 10000.0 * status_effect.stack_count as f64;
                                     rdps_data.multi_dmg.values.push(RdpsBuffData {
                                         caster: caster_encounter_entity.name.clone(),
+                                        buff_id: status_effect.status_effect_id,
                                         rate,
                                     });
                                     rdps_data.multi_dmg.sum_rate += rate;
@@ -1231,6 +1634,7 @@ impl EncounterState {
                                             val as f64 / 10000.0 * status_effect.stack_count as f64;
                                         rdps_data.multi_dmg.values.push(RdpsBuffData {
                                             caster: caster_encounter_entity.name.clone(),
+                                            buff_id: status_effect.status_effect_id,
                                             rate,
                                         });
                                         rdps_data.multi_dmg.sum_rate += rate;
@@ -1254,6 +1658,7 @@ impl EncounterState {
                                         (val as f64 / 10000.0) * status_effect.stack_count as f64;
                                     rdps_data.multi_dmg.values.push(RdpsBuffData {
                                         caster: caster_encounter_entity.name.clone(),
+                                        buff_id: status_effect.status_effect_id,
                                         rate,
                                     });
                                     rdps_data.multi_dmg.sum_rate += rate;
@@ -1271,6 +1676,7 @@ impl EncounterState {
                                         * status_effect.stack_count as f64;
                                     rdps_data.multi_dmg.values.push(RdpsBuffData {
                                         caster: caster_encounter_entity.name.clone(),
+                                        buff_id: status_effect.status_effect_id,
                                         rate,
                                     });
                                     rdps_data.multi_dmg.sum_rate += rate;
@@ -1285,6 +1691,7 @@ impl EncounterState {
                                         * status_effect.stack_count as f64;
                                     rdps_data.multi_dmg.values.push(RdpsBuffData {
                                         caster: caster_encounter_entity.name.clone(),
+                                        buff_id: status_effect.status_effect_id,
                                         rate,
                                     });
                                     rdps_data.multi_dmg.sum_rate += rate;
@@ -1353,6 +1760,7 @@ impl EncounterState {
                     let attack_power_amplify = if rdps_data.atk_pow_amplify.is_empty() {
                         RdpsBuffData {
                             caster: "".to_string(),
+                            buff_id: 0,
                             rate: 0.0,
                         }
                     } else {
@@ -1396,6 +1804,10 @@ impl EncounterState {
                             skill_id,
                             delta,
                             &mut skill_hit,
+                            "crit",
+                            crit.buff_id,
+                            non_boss_hit,
+                            non_boss_only,
                         );
                     }
 
@@ -1408,6 +1820,10 @@ impl EncounterState {
                             skill_id,
                             delta,
                             &mut skill_hit,
+                            "attack_power_sub_rate_2",
+                            dmg.buff_id,
+                            non_boss_hit,
+                            non_boss_only,
                         );
                     }
 
@@ -1420,6 +1836,10 @@ impl EncounterState {
                             skill_id,
                             delta,
                             &mut skill_hit,
+                            "skill_damage",
+                            dmg.buff_id,
+                            non_boss_hit,
+                            non_boss_only,
                         );
                     }
 
@@ -1433,6 +1853,10 @@ impl EncounterState {
                             skill_id,
                             delta,
                             &mut skill_hit,
+                            "multi_damage",
+                            dmg.buff_id,
+                            non_boss_hit,
+                            non_boss_only,
                         );
                     }
 
@@ -1447,6 +1871,10 @@ impl EncounterState {
                             skill_id,
                             delta,
                             &mut skill_hit,
+                            "attack_power_sub_rate_1",
+                            dmg.buff_id,
+                            non_boss_hit,
+                            non_boss_only,
                         );
                     }
 
@@ -1460,6 +1888,10 @@ impl EncounterState {
                             skill_id,
                             delta,
                             &mut skill_hit,
+                            "attack_power_amplify",
+                            attack_power_amplify.buff_id,
+                            non_boss_hit,
+                            non_boss_only,
                         );
                     }
                 } else if dmg_src_entity.entity_type == EntityType::PLAYER
@@ -1472,6 +1904,15 @@ impl EncounterState {
                         self.encounter.last_combat_packet - self.encounter.fight_start
                     );
                     self.rdps_valid = false;
+                    if self.rdps_invalidation.add(
+                        RdpsInvalidReason::MissingStats,
+                        Some(&dmg_src_entity.name),
+                        self.encounter.last_combat_packet - self.encounter.fight_start,
+                    ) {
+                        self.window
+                            .emit("rdps-invalid", &self.rdps_invalidation)
+                            .expect("failed to emit rdps-invalid");
+                    }
 
                     if !self.rdps_valid {
                         self.window
@@ -1488,6 +1929,19 @@ impl EncounterState {
                 self.encounter.encounter_damage_stats.top_damage_taken,
                 target_entity.damage_stats.damage_taken,
             );
+
+            self.record_incoming_hit(
+                &target_entity.name,
+                IncomingHit {
+                    timestamp: relative_timestamp as i64,
+                    source: source_entity.name.clone(),
+                    skill_id,
+                    skill_name: skill_name.clone(),
+                    damage,
+                    shield_absorbed: 0,
+                    hp_after: damage_data.target_current_hp,
+                },
+            );
         }
         // update current_boss
         else if target_entity.entity_type == EntityType::BOSS {
@@ -1542,7 +1996,113 @@ impl EncounterState {
             .insert(target_entity.name.clone(), target_entity);
     }
 
-    pub fn on_counterattack(&mut self, source_entity: &Entity) {
+    // (buffed by support, buffed by identity, debuffed by support)
+    fn get_support_buff_flags(&self, buff_ids: &[u32], debuff_ids: &[u32]) -> (bool, bool, bool) {
+        let from_support = |se: &StatusEffect| {
+            se.source
+                .skill
+                .as_ref()
+                .map_or(false, |skill| is_support_class_id(skill.class_id))
+                && se.buff_type & StatusEffectBuffTypeFlags::DMG.bits() != 0
+                && se.target == StatusEffectTarget::PARTY
+        };
+        let buffs = &self.encounter.encounter_damage_stats.buffs;
+        let debuffs = &self.encounter.encounter_damage_stats.debuffs;
+        let buffed_by = |category: &str| {
+            buff_ids
+                .iter()
+                .filter_map(|id| buffs.get(id))
+                .any(|buff| from_support(buff) && buff.buff_category == category)
+        };
+
+        (
+            buffed_by("classskill"),
+            buffed_by("identity"),
+            debuff_ids
+                .iter()
+                .filter_map(|id| debuffs.get(id))
+                .any(from_support),
+        )
+    }
+
+    fn on_invincible_hit(
+        &mut self,
+        dmg_src_entity: &Entity,
+        dmg_target_entity: &Entity,
+        skill_id: u32,
+        damage: i64,
+        timestamp: i64,
+    ) {
+        if dmg_src_entity.entity_type != EntityType::PLAYER
+            || (self.boss_only_damage && dmg_target_entity.entity_type != EntityType::BOSS)
+        {
+            return;
+        }
+
+        if dmg_target_entity.entity_type == EntityType::BOSS {
+            let relative_timestamp = (timestamp - self.encounter.fight_start) as i32;
+            let log = &mut self.encounter.encounter_damage_stats.boss_invincible_log;
+            match log.last_mut() {
+                Some(last) if relative_timestamp - last.1 <= INVINCIBLE_MERGE_MS => {
+                    last.1 = relative_timestamp;
+                }
+                _ => log.push((relative_timestamp, relative_timestamp)),
+            }
+        }
+
+        let source_entity = self
+            .encounter
+            .entities
+            .entry(dmg_src_entity.name.clone())
+            .or_insert_with(|| encounter_entity_from_entity(dmg_src_entity));
+        source_entity
+            .damage_stats
+            .wasted_damage
+            .add_hit(&HitFlag::INVINCIBLE, damage, 0);
+        // skills that only ever hit invincible targets aren't added to the skill list
+        let family_id = get_skill_family(skill_id);
+        if let Some(skill) = source_entity
+            .skills
+            .values_mut()
+            .find(|s| s.id == skill_id || get_skill_family(s.id) == family_id)
+        {
+            skill.wasted_damage.add_hit(&HitFlag::INVINCIBLE, damage, 0);
+        }
+    }
+
+    pub fn on_counterattack(&mut self, source_entity: &Entity, timestamp: i64) {
+        let relative_timestamp = if self.encounter.fight_start == 0 {
+            0
+        } else {
+            timestamp - self.encounter.fight_start
+        };
+        let target = self.encounter.current_boss_name.clone();
+
+        // a landed counter means the boss had a counter window open, so every
+        // other counter tripod cast into it is a failed attempt
+        let mut skill_id = 0;
+        let mut failed: Vec<(String, u32, i64)> = vec![];
+        for (name, cast_skill_id, cast_at) in self.counter_casts.drain(..) {
+            if cast_at > relative_timestamp || relative_timestamp - cast_at > COUNTER_WINDOW_MS {
+                continue;
+            }
+            if name == source_entity.name {
+                skill_id = cast_skill_id;
+            } else {
+                failed.push((name, cast_skill_id, cast_at));
+            }
+        }
+        for (name, cast_skill_id, cast_at) in failed {
+            if let Some(entity) = self.encounter.entities.get_mut(&name) {
+                entity.damage_stats.counter_log.push(CounterAttempt {
+                    timestamp: cast_at,
+                    skill_id: cast_skill_id,
+                    target: target.clone(),
+                    success: false,
+                });
+            }
+        }
+
         let entity = self
             .encounter
             .entities
@@ -1556,6 +2116,13 @@ impl EncounterState {
                 entity
             });
         entity.skill_stats.counters += 1;
+        // skill id is 0 when the counter didn't come from a counter tripod
+        entity.damage_stats.counter_log.push(CounterAttempt {
+            timestamp: relative_timestamp,
+            skill_id,
+            target,
+            success: true,
+        });
     }
 
     pub fn on_identity_gain(&mut self, pkt: &PKTIdentityGaugeChangeNotify) {
@@ -1641,6 +2208,421 @@ impl EncounterState {
     //     }
     // }
 
+    pub fn on_status_effect_applied(
+        &mut self,
+        source_entity: &Entity,
+        target_entity: &Entity,
+        status_effect: &StatusEffectDetails,
+        timestamp: i64,
+    ) {
+        if target_entity.entity_type != EntityType::PLAYER
+            && target_entity.entity_type != EntityType::BOSS
+        {
+            return;
+        }
+        let buff = match SKILL_BUFF_DATA.get(&status_effect.status_effect_id) {
+            Some(buff) => buff,
+            None => return,
+        };
+
+        if buff.buff_category == "battleitem" && source_entity.entity_type == EntityType::PLAYER {
+            self.on_battle_item_used(
+                source_entity,
+                status_effect.status_effect_id,
+                timestamp,
+                true,
+            );
+        }
+
+        if buff.buff_type == "heal"
+            && source_entity.entity_type == EntityType::PLAYER
+            && target_entity.entity_type == EntityType::PLAYER
+        {
+            self.on_heal_applied(source_entity, target_entity, status_effect, timestamp);
+        }
+
+        // same criteria as the buffs shown in the meter
+        if buff.icon_show_type == "none" {
+            return;
+        }
+
+        let buff_id = map_status_effect(status_effect, &mut self.custom_id_map);
+        let expire_at = status_effect.expire_at.map(|e| e.timestamp_millis());
+        let caster = if source_entity.entity_type == EntityType::PLAYER {
+            Some(source_entity.name.clone())
+        } else {
+            None
+        };
+        let key = (target_entity.name.clone(), status_effect.instance_id);
+        // effect was re-applied without a remove, close the old one first
+        if let Some((old_id, start, old_expire_at, old_caster)) = self
+            .active_status_effects
+            .insert(key, (buff_id, timestamp, expire_at, caster))
+        {
+            let end = old_expire_at.map_or(timestamp, |e| e.min(timestamp));
+            self.log_status_effect(&target_entity.name, old_caster, old_id, start, end);
+        }
+    }
+
+    pub fn on_status_effects_removed(
+        &mut self,
+        target_entity: &Entity,
+        instance_ids: &[u32],
+        timestamp: i64,
+    ) {
+        for instance_id in instance_ids {
+            if let Some((buff_id, start, expire_at, caster)) = self
+                .active_status_effects
+                .remove(&(target_entity.name.clone(), *instance_id))
+            {
+                let end = expire_at.map_or(timestamp, |e| e.min(timestamp));
+                self.log_status_effect(&target_entity.name, caster, buff_id, start, end);
+            }
+        }
+
+        let removed_heals: Vec<(String, String, u32)> = self
+            .heal_sources
+            .iter()
+            .filter(|((target, _, _), (instance_id, _, _, _))| {
+                *target == target_entity.name && instance_ids.contains(instance_id)
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in removed_heals {
+            if let Some((_, amount_left, _, _)) = self.heal_sources.remove(&key) {
+                self.settle_heal(&key.1, amount_left);
+            }
+        }
+    }
+
+    fn on_heal_applied(
+        &mut self,
+        source_entity: &Entity,
+        target_entity: &Entity,
+        status_effect: &StatusEffectDetails,
+        timestamp: i64,
+    ) {
+        let active_until = status_effect
+            .expire_at
+            .map_or(timestamp + HEAL_ATTRIBUTION_MS, |e| e.timestamp_millis());
+        // value is the heal's full amount, hp updates use it up as effective healing
+        let amount = Some(status_effect.value as i64).filter(|amount| *amount > 0);
+        let key = (
+            target_entity.name.clone(),
+            source_entity.name.clone(),
+            status_effect.status_effect_id,
+        );
+        // the same heal re-applied replaces the old one
+        if let Some((_, amount_left, _, _)) = self.heal_sources.insert(
+            key,
+            (status_effect.instance_id, amount, timestamp, active_until),
+        ) {
+            self.settle_heal(&source_entity.name, amount_left);
+        }
+    }
+
+    // whatever a heal didn't restore by the time it ended was overheal
+    fn settle_heal(&mut self, source_name: &str, amount_left: Option<i64>) {
+        let amount_left = match amount_left {
+            Some(amount_left) if amount_left > 0 => amount_left,
+            _ => return,
+        };
+        if let Some(source) = self.encounter.entities.get_mut(source_name) {
+            source.damage_stats.overheal += amount_left;
+        }
+    }
+
+    pub fn on_hp_change(&mut self, target_entity: &Entity, hp: i64, max_hp: i64, timestamp: i64) {
+        if target_entity.entity_type != EntityType::PLAYER || self.encounter.fight_start == 0 {
+            return;
+        }
+
+        let expired_heals: Vec<(String, String, u32)> = self
+            .heal_sources
+            .iter()
+            .filter(|((target, _, _), (_, _, _, active_until))| {
+                *target == target_entity.name && *active_until < timestamp
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in expired_heals {
+            if let Some((_, amount_left, _, _)) = self.heal_sources.remove(&key) {
+                self.settle_heal(&key.1, amount_left);
+            }
+        }
+
+        let gained = match self.encounter.entities.get_mut(&target_entity.name) {
+            Some(entity) => {
+                let (previous_hp, previous_max_hp) = (entity.current_hp, entity.max_hp);
+                entity.current_hp = hp;
+                entity.max_hp = max_hp;
+                // hp coming back from 0 is a revive, and max hp buffs raise hp without healing
+                if entity.is_dead
+                    || previous_hp <= 0
+                    || hp <= previous_hp
+                    || max_hp != previous_max_hp
+                {
+                    return;
+                }
+                hp - previous_hp
+            }
+            None => return,
+        };
+
+        // passive regen and other hp gain without an active heal isn't healing,
+        // the gain is split across the target's active heals, oldest first
+        let mut heals: Vec<(&(String, String, u32), &mut (u32, Option<i64>, i64, i64))> = self
+            .heal_sources
+            .iter_mut()
+            .filter(|((target, _, _), _)| *target == target_entity.name)
+            .collect();
+        heals.sort_by_key(|(_, (_, _, applied_at, _))| *applied_at);
+        let mut gain_left = gained;
+        let mut credits: Vec<(String, u32, i64)> = vec![];
+        for ((_, source_name, buff_id), (_, amount_left, _, _)) in heals {
+            if gain_left == 0 {
+                break;
+            }
+            // heals without a known amount are credited with everything left
+            let healed = match amount_left {
+                Some(amount_left) => {
+                    let healed = gain_left.min(*amount_left);
+                    *amount_left -= healed;
+                    healed
+                }
+                None => gain_left,
+            };
+            gain_left -= healed;
+            if healed > 0 {
+                credits.push((source_name.clone(), *buff_id, healed));
+            }
+        }
+
+        for (source_name, buff_id, healed) in credits {
+            if let Some(target) = self.encounter.entities.get_mut(&target_entity.name) {
+                target.damage_stats.healing_received += healed;
+                target
+                    .damage_stats
+                    .healing_received_by
+                    .entry(buff_id)
+                    .and_modify(|e| *e += healed)
+                    .or_insert(healed);
+                target
+                    .damage_stats
+                    .healing_received_from
+                    .entry(source_name.clone())
+                    .and_modify(|e| *e += healed)
+                    .or_insert(healed);
+            }
+            if let Some(source) = self.encounter.entities.get_mut(&source_name) {
+                source.damage_stats.healing_done += healed;
+                source
+                    .damage_stats
+                    .healing_done_by
+                    .entry(buff_id)
+                    .and_modify(|e| *e += healed)
+                    .or_insert(healed);
+                source
+                    .damage_stats
+                    .healing_done_to
+                    .entry(target_entity.name.clone())
+                    .and_modify(|e| *e += healed)
+                    .or_insert(healed);
+            }
+        }
+    }
+
+    pub fn on_esther_used(&mut self, esther: &Entity, triggered_by: &Entity, timestamp: i64) {
+        if self.encounter.fight_start == 0 || esther.entity_type != EntityType::ESTHER {
+            return;
+        }
+        let triggered_by = if triggered_by.entity_type == EntityType::PLAYER {
+            Some(triggered_by.name.as_str())
+        } else {
+            None
+        };
+        self.get_esther_use(&esther.name, triggered_by, timestamp);
+    }
+
+    pub fn on_paralyzation_change(
+        &mut self,
+        object_id: u64,
+        paralyzation_point: i64,
+        timestamp: i64,
+    ) {
+        let previous = self
+            .paralyzation_points
+            .insert(object_id, paralyzation_point)
+            .unwrap_or_default();
+        let stagger = paralyzation_point - previous;
+        if self.encounter.fight_start == 0 || stagger <= 0 {
+            return;
+        }
+        // adds can be staggered by the same esther hits, only the boss counts
+        let is_current_boss = self
+            .encounter
+            .entities
+            .get(&self.encounter.current_boss_name)
+            .map_or(false, |boss| boss.id == object_id);
+        if !is_current_boss {
+            return;
+        }
+
+        let esther_name = match self.last_esther_hit.as_ref() {
+            Some((name, hit_at)) if timestamp - hit_at <= ESTHER_STAGGER_WINDOW_MS => name.clone(),
+            _ => return,
+        };
+        self.get_esther_use(&esther_name, None, timestamp).stagger += stagger;
+    }
+
+    // latest activation of this esther, or a new one if it was used a while ago
+    fn get_esther_use(
+        &mut self,
+        name: &str,
+        triggered_by: Option<&str>,
+        timestamp: i64,
+    ) -> &mut EstherUse {
+        let relative_timestamp = timestamp - self.encounter.fight_start;
+        let (boss_name, boss_hp_percent, boss_hp_bars) = self.get_boss_snapshot();
+        let log = &mut self.encounter.encounter_damage_stats.esther_log;
+        let index = match log.iter().rposition(|esther| {
+            esther.name == name && relative_timestamp - esther.timestamp <= ESTHER_MERGE_MS
+        }) {
+            Some(index) => index,
+            None => {
+                log.push(EstherUse {
+                    timestamp: relative_timestamp,
+                    name: name.to_string(),
+                    boss_name,
+                    boss_hp_percent,
+                    boss_hp_bars,
+                    ..Default::default()
+                });
+                log.len() - 1
+            }
+        };
+
+        let esther = &mut log[index];
+        if let Some(triggered_by) = triggered_by {
+            if esther.triggered_by.is_empty() {
+                esther.triggered_by = triggered_by.to_string();
+            }
+        }
+        esther
+    }
+
+    // current boss name, hp % and hp bars left
+    fn get_boss_snapshot(&self) -> (String, f32, Option<u32>) {
+        let boss_name = self.encounter.current_boss_name.clone();
+        let boss = self
+            .encounter
+            .entities
+            .get(&boss_name)
+            .filter(|boss| boss.max_hp > 0);
+        let hp_percent = boss
+            .map(|boss| boss.current_hp.max(0) as f32 / boss.max_hp as f32 * 100.0)
+            .unwrap_or_default();
+        let hp_bars = boss.and_then(|boss| {
+            let bars = BOSS_HP_BARS.get(boss_name.as_str())?;
+            let bar_hp = max(boss.max_hp / *bars as i64, 1);
+            Some(((boss.current_hp.max(0) + bar_hp - 1) / bar_hp) as u32)
+        });
+        (boss_name, hp_percent, hp_bars)
+    }
+
+    pub fn on_battle_item_used(
+        &mut self,
+        source_entity: &Entity,
+        item_id: u32,
+        timestamp: i64,
+        effect_applied: bool,
+    ) {
+        if self.encounter.fight_start == 0 || source_entity.entity_type != EntityType::PLAYER {
+            return;
+        }
+        let item_type = match get_battle_item_type(item_id) {
+            Some(item_type) => item_type,
+            None => return,
+        };
+
+        let relative_timestamp = timestamp - self.encounter.fight_start;
+        let entity = self
+            .encounter
+            .entities
+            .entry(source_entity.name.clone())
+            .or_insert_with(|| encounter_entity_from_entity(source_entity));
+        let log = &mut entity.damage_stats.battle_item_log;
+        if let Some(item) = log.iter_mut().rev().find(|item| {
+            item.item_id == item_id && relative_timestamp - item.timestamp <= BATTLE_ITEM_MERGE_MS
+        }) {
+            if effect_applied {
+                item.effects_applied += 1;
+            }
+            return;
+        }
+
+        log.push(BattleItemUse {
+            timestamp: relative_timestamp,
+            item_id,
+            name: SKILL_EFFECT_DATA
+                .get(&item_id)
+                .and_then(|item| item.item_name.clone())
+                .unwrap_or_default(),
+            item_type: item_type.to_string(),
+            effects_applied: effect_applied as i64,
+            ..Default::default()
+        });
+    }
+
+    // target left view, so every effect on it is gone
+    pub fn on_status_effects_cleared(&mut self, target_entity: &Entity, timestamp: i64) {
+        let instance_ids = self
+            .active_status_effects
+            .keys()
+            .filter(|(name, _)| *name == target_entity.name)
+            .map(|(_, instance_id)| *instance_id)
+            .collect::<Vec<_>>();
+        self.on_status_effects_removed(target_entity, &instance_ids, timestamp);
+    }
+
+    fn log_status_effect(
+        &mut self,
+        target_name: &str,
+        caster: Option<String>,
+        buff_id: u32,
+        start: i64,
+        end: i64,
+    ) {
+        if self.encounter.fight_start == 0 {
+            return;
+        }
+        let start = max(start, self.encounter.fight_start);
+        if end <= start {
+            return;
+        }
+        let interval = (
+            (start - self.encounter.fight_start) as i32,
+            (end - self.encounter.fight_start) as i32,
+        );
+
+        self.status_effect_log
+            .entry(target_name.to_string())
+            .or_default()
+            .entry(buff_id)
+            .or_default()
+            .push(interval);
+        if let Some(caster) = caster {
+            self.caster_status_effect_log
+                .entry(caster)
+                .or_default()
+                .entry(target_name.to_string())
+                .or_default()
+                .entry(buff_id)
+                .or_default()
+                .push(interval);
+        }
+    }
+
     pub fn on_boss_shield(&mut self, target_entity: &Entity, shield: u64) {
         if target_entity.entity_type == EntityType::BOSS
             && target_entity.name == self.encounter.current_boss_name
@@ -1661,6 +2643,11 @@ impl EncounterState {
         buff_id: u32,
         shield: u64,
     ) {
+        if source_entity.entity_type == EntityType::ESTHER && self.encounter.fight_start > 0 {
+            self.get_esther_use(&source_entity.name, None, Utc::now().timestamp_millis())
+                .shield += shield;
+        }
+
         if source_entity.entity_type == EntityType::PLAYER
             && target_entity.entity_type == EntityType::PLAYER
         {
@@ -1753,6 +2740,7 @@ impl EncounterState {
         target_entity: &Entity,
         buff_id: u32,
         shield_removed: u64,
+        timestamp: i64,
     ) {
         if source_entity.entity_type == EntityType::PLAYER
             && target_entity.entity_type == EntityType::PLAYER
@@ -1813,12 +2801,138 @@ impl EncounterState {
                     .insert(source_entity_state.name.clone(), source_entity_state);
             }
 
+            // shield updates carry no hit reference, so this is approximate: the absorb
+            // goes to the latest hit taken within a short window of the update
+            let relative_timestamp = timestamp - self.encounter.fight_start;
+            if let Some(hit) = self
+                .incoming_hits
+                .get_mut(&target_entity.name)
+                .and_then(|hits| {
+                    hits.iter_mut().rev().find(|hit| {
+                        (relative_timestamp - hit.timestamp).abs() <= SHIELD_HIT_MATCH_MS
+                    })
+                })
+            {
+                hit.shield_absorbed += shield_removed;
+            }
+
             self.encounter
                 .encounter_damage_stats
                 .total_effective_shielding += shield_removed;
         }
     }
 
+    // rolling window of hits taken, snapshotted on death
+    fn record_incoming_hit(&mut self, target_name: &str, hit: IncomingHit) {
+        let hits = self
+            .incoming_hits
+            .entry(target_name.to_string())
+            .or_default();
+        while hits.front().map_or(false, |h| {
+            h.timestamp < hit.timestamp - DEATH_RECAP_WINDOW_MS
+        }) {
+            hits.pop_front();
+        }
+        hits.push_back(hit);
+    }
+
+    pub fn invalidate_rdps(&mut self, reason: RdpsInvalidReason) {
+        self.rdps_valid = false;
+        if self.encounter.fight_start > 0 && self.rdps_invalidation.add(reason, None, 0) {
+            self.window
+                .emit("rdps-invalid", &self.rdps_invalidation)
+                .expect("failed to emit rdps-invalid");
+        }
+    }
+
+    // smoothed time to kill and time to next hp bar for the current boss
+    pub fn update_kill_projection(&mut self, timestamp: i64) {
+        let boss_name = self.encounter.current_boss_name.clone();
+        let boss = self
+            .encounter
+            .entities
+            .get(&boss_name)
+            .filter(|boss| !boss.is_dead && boss.current_hp > 0)
+            .map(|boss| (boss.current_hp, boss.current_shield as i64, boss.max_hp));
+        let (current_hp, current_shield, max_hp) = match boss {
+            Some(boss) if self.encounter.fight_start > 0 => boss,
+            _ => {
+                self.encounter.kill_projection = None;
+                return;
+            }
+        };
+        let relative_timestamp = (timestamp - self.encounter.fight_start) as i32;
+
+        let mut hp_per_second = 0.0;
+        if let Some(log) = self.boss_hp_log.get(&boss_name) {
+            let window_start = relative_timestamp / 1000 - KILL_PROJECTION_WINDOW_S;
+            if let (Some(first), Some(last)) =
+                (log.iter().find(|hp| hp.time >= window_start), log.last())
+            {
+                if last.time > first.time {
+                    hp_per_second = (first.hp - last.hp) as f64 / (last.time - first.time) as f64;
+                }
+            }
+        }
+        // fall back to party dps if the boss hasn't lost hp recently
+        if hp_per_second <= 0.0 && relative_timestamp > 0 {
+            let party_damage: i64 = self
+                .encounter
+                .entities
+                .values()
+                .filter(|e| e.entity_type == EntityType::PLAYER && !e.is_dead)
+                .map(|e| e.damage_stats.damage_dealt)
+                .sum();
+            hp_per_second = party_damage as f64 / (relative_timestamp as f64 / 1000.0);
+        }
+        if hp_per_second <= 0.0 {
+            self.encounter.kill_projection = None;
+            return;
+        }
+
+        if self.kill_projection_boss != boss_name {
+            self.kill_projection_log.clear();
+            self.kill_projection_boss.clone_from(&boss_name);
+        } else if let Some(prev) = self.encounter.kill_projection.as_ref() {
+            hp_per_second = prev.hp_per_second
+                + KILL_PROJECTION_SMOOTHING * (hp_per_second - prev.hp_per_second);
+        }
+        if hp_per_second < KILL_PROJECTION_MIN_HP_PER_SECOND {
+            self.encounter.kill_projection = None;
+            return;
+        }
+
+        let time_to_kill =
+            ((current_hp + current_shield) as f64 / hp_per_second * 1000.0).round() as i64;
+        let time_to_next_bar = BOSS_HP_BARS
+            .get(boss_name.as_str())
+            .filter(|_| max_hp > 0)
+            .map(|bars| {
+                let bar_hp = max(max_hp / *bars as i64, 1);
+                let hp_in_bar = match current_hp % bar_hp {
+                    0 => bar_hp,
+                    hp => hp,
+                };
+                (hp_in_bar as f64 / hp_per_second * 1000.0).round() as i64
+            });
+
+        if self
+            .kill_projection_log
+            .last()
+            .map_or(true, |(t, _)| t / 1000 != relative_timestamp / 1000)
+        {
+            let projected_kill =
+                relative_timestamp.saturating_add(time_to_kill.min(i32::MAX as i64) as i32);
+            self.kill_projection_log
+                .push((relative_timestamp, projected_kill));
+        }
+        self.encounter.kill_projection = Some(KillProjection {
+            hp_per_second,
+            time_to_kill,
+            time_to_next_bar,
+        });
+    }
+
     pub fn save_to_db(&mut self, player_stats: Option<Cache<String, PlayerStats>>, manual: bool) {
         if !manual {
             if self.encounter.fight_start == 0
@@ -1858,9 +2972,42 @@ impl EncounterState {
         let prev_stagger = self.prev_stagger;
 
         let damage_log = self.damage_log.clone();
+        let activity_log = self.activity_log.clone();
         let identity_log = self.identity_log.clone();
         let cast_log = self.cast_log.clone();
         let boss_hp_log = self.boss_hp_log.clone();
+
+        // close anything still running at the end of the fight
+        let mut status_effect_log = self.status_effect_log.clone();
+        let mut caster_status_effect_log = self.caster_status_effect_log.clone();
+        let fight_start = self.encounter.fight_start;
+        let fight_end = self.encounter.last_combat_packet;
+        for ((target_name, _), (buff_id, start, expire_at, caster)) in
+            self.active_status_effects.iter()
+        {
+            let start = max(*start, fight_start);
+            let end = expire_at.map_or(fight_end, |e| e.min(fight_end));
+            if end > start {
+                let interval = ((start - fight_start) as i32, (end - fight_start) as i32);
+                status_effect_log
+                    .entry(target_name.clone())
+                    .or_default()
+                    .entry(*buff_id)
+                    .or_default()
+                    .push(interval);
+                if let Some(caster) = caster {
+                    caster_status_effect_log
+                        .entry(caster.clone())
+                        .or_default()
+                        .entry(target_name.clone())
+                        .or_default()
+                        .entry(*buff_id)
+                        .or_default()
+                        .push(interval);
+                }
+            }
+        }
+
         let stagger_log = self.stagger_log.clone();
         let stagger_intervals = self.stagger_intervals.clone();
         let raid_clear = self.raid_clear;
@@ -1872,6 +3019,18 @@ impl EncounterState {
         let ntp_fight_start = self.ntp_fight_start;
 
         let rdps_valid = self.rdps_valid;
+        let rdps_invalidation = self.rdps_invalidation.clone();
+        let rdps_local = self.local_rdps;
+
+        let kill_projection_accuracy =
+            if raid_clear && self.kill_projection_boss == encounter.current_boss_name {
+                get_kill_projection_accuracy(
+                    &self.kill_projection_log,
+                    (encounter.last_combat_packet - encounter.fight_start) as i32,
+                )
+            } else {
+                None
+            };
 
         let skill_cast_log = self.skill_tracker.get_cast_log();
 
@@ -1892,6 +3051,7 @@ impl EncounterState {
                 encounter,
                 prev_stagger,
                 damage_log,
+                activity_log,
                 identity_log,
                 cast_log,
                 boss_hp_log,
@@ -1907,6 +3067,11 @@ impl EncounterState {
                 rdps_valid,
                 manual,
                 skill_cast_log,
+                status_effect_log,
+                caster_status_effect_log,
+                kill_projection_accuracy,
+                rdps_invalidation,
+                rdps_local,
             );
 
             tx.commit().expect("failed to commit transaction");
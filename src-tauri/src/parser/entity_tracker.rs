@@ -6,8 +6,7 @@ use crate::parser::models::{
 };
 use crate::parser::party_tracker::PartyTracker;
 use crate::parser::status_tracker::{
-    build_status_effect, StatusEffectDetails, StatusEffectTargetType, StatusEffectType,
-    StatusTracker,
+    build_status_effect, StatusEffectDetails, StatusEffectTargetType, StatusTracker,
 };
 
 use chrono::{DateTime, Utc};
@@ -283,7 +282,7 @@ impl EntityTracker {
         entities: &HashMap<String, EncounterEntity>,
     ) -> Vec<StatusEffectDetails> {
         let timestamp = Utc::now();
-        let mut shields: Vec<StatusEffectDetails> = Vec::new();
+        let mut status_effects: Vec<StatusEffectDetails> = Vec::new();
         for sed in pkt.status_effect_datas {
             let entity = self.get_source_entity(sed.source_id);
             let encounter_entity = entities.get(&entity.name);
@@ -296,14 +295,12 @@ impl EntityTracker {
                 timestamp,
                 encounter_entity,
             );
-            if status_effect.status_effect_type == StatusEffectType::Shield {
-                shields.push(status_effect.clone());
-            }
+            status_effects.push(status_effect.clone());
             self.status_tracker
                 .borrow_mut()
                 .register_status_effect(status_effect);
         }
-        shields
+        status_effects
     }
 
     pub fn party_status_effect_remove(
@@ -334,7 +331,7 @@ impl EntityTracker {
     pub fn new_trap(&mut self, pkt: &PKTNewTrap) {
         let trap: Entity = Entity {
             id: pkt.trap_struct.object_id,
-            entity_type: PROJECTILE,
+            entity_type: TRAP,
             name: format!("{:x}", pkt.trap_struct.object_id),
             owner_id: pkt.trap_struct.owner_id,
             skill_id: pkt.trap_struct.skill_id,
@@ -411,7 +408,10 @@ impl EntityTracker {
 
     pub fn get_source_entity(&mut self, id: u64) -> Entity {
         let id = if let Some(entity) = self.entities.get(&id) {
-            if entity.entity_type == PROJECTILE || entity.entity_type == SUMMON {
+            if entity.entity_type == PROJECTILE
+                || entity.entity_type == SUMMON
+                || entity.entity_type == TRAP
+            {
                 entity.owner_id
             } else {
                 id
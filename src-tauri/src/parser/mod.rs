@@ -110,6 +110,11 @@ pub fn start(
             duration = Duration::from_millis(1500);
             info!("low performance mode enabled")
         }
+        if settings.general.local_rdps {
+            stats_api.local_rdps = true;
+            state.local_rdps = true;
+            info!("local rdps enabled")
+        }
     }
 
     // read saved local players
@@ -233,7 +238,7 @@ pub fn start(
                     parse_pkt(&data, PKTCounterAttackNotify::new, "PKTCounterAttackNotify")
                 {
                     if let Some(entity) = entity_tracker.entities.get(&pkt.source_id) {
-                        state.on_counterattack(entity);
+                        state.on_counterattack(entity, Utc::now().timestamp_millis());
                     }
                 }
             }
@@ -327,6 +332,7 @@ pub fn start(
                         local_players.insert(entity.character_id, entity.name.clone());
                         write_local_players(&local_players, &local_player_path)?;
                     }
+                    stats_api.update_local_stats(&entity);
                     state.on_init_pc(entity, hp, max_hp)
                 }
             }
@@ -364,6 +370,7 @@ pub fn start(
                             stats_api.sync(local_player, &state);
                         }
                     }
+                    stats_api.update_local_stats(&entity);
                     state.on_new_pc(entity, hp, max_hp);
                 }
             }
@@ -382,6 +389,10 @@ pub fn start(
                 if let Some(pkt) = parse_pkt(&data, PKTNewNpcSummon::new, "PKTNewNpcSummon") {
                     let (hp, max_hp) = get_current_and_max_hp(&pkt.npc_struct.stat_pairs);
                     let entity = entity_tracker.new_npc_summon(pkt, max_hp);
+                    if entity.entity_type == EntityType::ESTHER {
+                        let owner = entity_tracker.get_source_entity(entity.owner_id);
+                        state.on_esther_used(&entity, &owner, Utc::now().timestamp_millis());
+                    }
                     debug_print(format_args!(
                         "new {}: {}, eid: {}, id: {}, hp: {}",
                         entity.entity_type, entity.name, entity.id, entity.npc_id, max_hp
@@ -392,6 +403,15 @@ pub fn start(
             Pkt::NewProjectile => {
                 if let Some(pkt) = parse_pkt(&data, PKTNewProjectile::new, "PKTNewProjectile") {
                     entity_tracker.new_projectile(&pkt);
+                    if let Some(owner) = entity_tracker.get_entity_ref(pkt.projectile_info.owner_id)
+                    {
+                        state.on_battle_item_used(
+                            owner,
+                            pkt.projectile_info.skill_effect,
+                            Utc::now().timestamp_millis(),
+                            false,
+                        );
+                    }
                     if entity_tracker.id_is_player(pkt.projectile_info.owner_id)
                         && pkt.projectile_info.skill_id > 0
                     {
@@ -408,6 +428,14 @@ pub fn start(
             Pkt::NewTrap => {
                 if let Some(pkt) = parse_pkt(&data, PKTNewTrap::new, "PKTNewTrap") {
                     entity_tracker.new_trap(&pkt);
+                    if let Some(owner) = entity_tracker.get_entity_ref(pkt.trap_struct.owner_id) {
+                        state.on_battle_item_used(
+                            owner,
+                            pkt.trap_struct.skill_effect,
+                            Utc::now().timestamp_millis(),
+                            false,
+                        );
+                    }
                     if entity_tracker.id_is_player(pkt.trap_struct.owner_id)
                         && pkt.trap_struct.skill_id > 0
                     {
@@ -421,24 +449,30 @@ pub fn start(
                     }
                 }
             }
-            // Pkt::ParalyzationStateNotify => {
-            //     if let Some(pkt) = parse_pkt(
-            //         &data,
-            //         PKTParalyzationStateNotify::new,
-            //         "PKTParalyzationStateNotify",
-            //     ) {
-            //         state.on_stagger_change(&pkt);
-            //         if emit_details.load(Ordering::Relaxed) {
-            //             window.emit(
-            //                 "stagger-update",
-            //                 Stagger {
-            //                     current: pkt.paralyzation_point,
-            //                     max: pkt.paralyzation_max_point,
-            //                 },
-            //             )?;
-            //         }
-            //     }
-            // }
+            Pkt::ParalyzationStateNotify => {
+                if let Some(pkt) = parse_pkt(
+                    &data,
+                    PKTParalyzationStateNotify::new,
+                    "PKTParalyzationStateNotify",
+                ) {
+                    // the stagger meter is still off, this only credits stagger to esthers
+                    state.on_paralyzation_change(
+                        pkt.object_id,
+                        pkt.paralyzation_point as i64,
+                        Utc::now().timestamp_millis(),
+                    );
+                    // state.on_stagger_change(&pkt);
+                    // if emit_details.load(Ordering::Relaxed) {
+                    //     window.emit(
+                    //         "stagger-update",
+                    //         Stagger {
+                    //             current: pkt.paralyzation_point,
+                    //             max: pkt.paralyzation_max_point,
+                    //         },
+                    //     )?;
+                    // }
+                }
+            }
             Pkt::RaidBegin => {
                 if let Some(pkt) = parse_pkt(&data, PKTRaidBegin::new, "PKTRaidBegin") {
                     debug_print(format_args!("raid begin: {}", pkt.raid_id));
@@ -559,6 +593,12 @@ pub fn start(
                         .borrow()
                         .get_local_character_id(entity_tracker.local_entity_id);
                     let target_count = pkt.skill_damage_abnormal_move_events.len() as i32;
+                    let target_ids: Vec<u64> = pkt
+                        .skill_damage_abnormal_move_events
+                        .iter()
+                        .map(|event| event.skill_damage_event.target_id)
+                        .collect();
+                    let primary_target = state.get_primary_target(&target_ids, &entity_tracker);
                     let player_stats = stats_api.get_stats(&state);
                     for event in pkt.skill_damage_abnormal_move_events.iter() {
                         let target_entity =
@@ -586,6 +626,7 @@ pub fn start(
                             se_on_source,
                             se_on_target,
                             target_count,
+                            event.skill_damage_event.target_id == primary_target,
                             &entity_tracker,
                             &player_stats,
                             now,
@@ -608,6 +649,12 @@ pub fn start(
                         .borrow()
                         .get_local_character_id(entity_tracker.local_entity_id);
                     let target_count = pkt.skill_damage_events.len() as i32;
+                    let target_ids: Vec<u64> = pkt
+                        .skill_damage_events
+                        .iter()
+                        .map(|event| event.target_id)
+                        .collect();
+                    let primary_target = state.get_primary_target(&target_ids, &entity_tracker);
                     let player_stats = stats_api.get_stats(&state);
                     for event in pkt.skill_damage_events.iter() {
                         let target_entity = entity_tracker.get_or_create_entity(event.target_id);
@@ -634,6 +681,7 @@ pub fn start(
                             se_on_source,
                             se_on_target,
                             target_count,
+                            event.target_id == primary_target,
                             &entity_tracker,
                             &player_stats,
                             now,
@@ -669,8 +717,10 @@ pub fn start(
                     "PKTPartyStatusEffectAddNotify",
                 ) {
                     // info!("{:?}", pkt);
-                    let shields = entity_tracker.party_status_effect_add(pkt, &state.encounter.entities);
-                    for status_effect in shields {
+                    let status_effects =
+                        entity_tracker.party_status_effect_add(pkt, &state.encounter.entities);
+                    let now = Utc::now().timestamp_millis();
+                    for status_effect in status_effects {
                         let source = entity_tracker.get_source_entity(status_effect.source_id);
                         let target_id =
                             if status_effect.target_type == StatusEffectTargetType::Party {
@@ -682,6 +732,10 @@ pub fn start(
                                 status_effect.target_id
                             };
                         let target = entity_tracker.get_source_entity(target_id);
+                        state.on_status_effect_applied(&source, &target, &status_effect, now);
+                        if status_effect.status_effect_type != StatusEffectType::Shield {
+                            continue;
+                        }
                         // info!("SHIELD SOURCE: {} > TARGET: {}", source.name, target.name);
                         state.on_boss_shield(&target, status_effect.value);
                         state.on_shield_applied(
@@ -700,8 +754,18 @@ pub fn start(
                     "PKTPartyStatusEffectRemoveNotify",
                 ) {
                     let character_id = pkt.character_id;
+                    let instance_ids = pkt.status_effect_instance_ids.clone();
                     let (is_shield, shields_broken, left_workshop) =
                         entity_tracker.party_status_effect_remove(pkt);
+                    if let Some(entity_id) = id_tracker.borrow().get_entity_id(character_id) {
+                        if let Some(entity) = entity_tracker.get_entity_ref(entity_id) {
+                            state.on_status_effects_removed(
+                                entity,
+                                &instance_ids,
+                                Utc::now().timestamp_millis(),
+                            );
+                        }
+                    }
                     if left_workshop {
                         if let Some(entity_id) = id_tracker.borrow().get_entity_id(character_id) {
                             if let Some(entity) = entity_tracker.get_entity_ref(entity_id) {
@@ -751,6 +815,15 @@ pub fn start(
                         Utc::now(),
                         Some(&state.encounter.entities),
                     );
+                    let source = entity_tracker.get_source_entity(status_effect.source_id);
+                    if let Some(target) = entity_tracker.get_entity_ref(pkt.object_id) {
+                        state.on_status_effect_applied(
+                            &source,
+                            target,
+                            &status_effect,
+                            Utc::now().timestamp_millis(),
+                        );
+                    }
                     if status_effect.status_effect_type == StatusEffectType::Shield {
                         let source = entity_tracker.get_source_entity(status_effect.source_id);
                         let target_id =
@@ -793,6 +866,13 @@ pub fn start(
                     PKTStatusEffectRemoveNotify::new,
                     "PKTStatusEffectRemoveNotify",
                 ) {
+                    if let Some(target) = entity_tracker.get_entity_ref(pkt.object_id) {
+                        state.on_status_effects_removed(
+                            target,
+                            &pkt.status_effect_instance_ids,
+                            Utc::now().timestamp_millis(),
+                        );
+                    }
                     let (is_shield, shields_broken, left_workshop) =
                         status_tracker.borrow_mut().remove_status_effects(
                             pkt.object_id,
@@ -921,6 +1001,9 @@ pub fn start(
                     PKTZoneObjectUnpublishNotify::new,
                     "PKTZoneObjectUnpublishNotify",
                 ) {
+                    if let Some(entity) = entity_tracker.get_entity_ref(pkt.object_id) {
+                        state.on_status_effects_cleared(entity, Utc::now().timestamp_millis());
+                    }
                     status_tracker
                         .borrow_mut()
                         .remove_local_object(pkt.object_id);
@@ -963,6 +1046,16 @@ pub fn start(
                     "PKTTroopMemberUpdateMinNotify",
                 ) {
                     // info!("{:?}", pkt);
+                    if let Some(object_id) = id_tracker.borrow().get_entity_id(pkt.character_id) {
+                        if let Some(entity) = entity_tracker.get_entity_ref(object_id) {
+                            state.on_hp_change(
+                                entity,
+                                pkt.cur_hp,
+                                pkt.max_hp,
+                                Utc::now().timestamp_millis(),
+                            );
+                        }
+                    }
                     for se in pkt.status_effect_datas.iter() {
                         if let Some(object_id) = id_tracker.borrow().get_entity_id(pkt.character_id)
                         {
@@ -1001,6 +1094,10 @@ pub fn start(
             if state.boss_dead_update {
                 state.boss_dead_update = false;
             }
+            state.update_kill_projection(Utc::now().timestamp_millis());
+            if let Some(reason) = stats_api.rdps_invalid_reason(&state.raid_difficulty) {
+                state.invalidate_rdps(reason);
+            }
             let mut clone = state.encounter.clone();
             let window = window.clone();
 
@@ -1157,7 +1254,13 @@ fn on_shield_change(
     };
     let target = entity_tracker.get_source_entity(target_id);
     state.on_boss_shield(&target, status_effect.value);
-    state.on_shield_used(&source, &target, status_effect.status_effect_id, change);
+    state.on_shield_used(
+        &source,
+        &target,
+        status_effect.status_effect_id,
+        change,
+        Utc::now().timestamp_millis(),
+    );
 }
 
 fn write_local_players(local_players: &HashMap<u64, String>, path: &PathBuf) -> Result<()> {
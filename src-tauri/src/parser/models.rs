@@ -1,8 +1,10 @@
 use std::fmt::Display;
+use std::hash::Hash;
 use std::str::FromStr;
 
 use crate::parser::entity_tracker::Entity;
 use bitflags::bitflags;
+use hashbrown::hash_map::Entry;
 use hashbrown::{HashMap, HashSet};
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
@@ -24,6 +26,7 @@ pub enum EntityType {
     ESTHER,
     PROJECTILE,
     SUMMON,
+    TRAP,
 }
 
 impl Display for EntityType {
@@ -38,6 +41,7 @@ impl Display for EntityType {
             EntityType::ESTHER => "ESTHER".to_string(),
             EntityType::PROJECTILE => "PROJECTILE".to_string(),
             EntityType::SUMMON => "SUMMON".to_string(),
+            EntityType::TRAP => "TRAP".to_string(),
         };
         write!(f, "{}", str)
     }
@@ -75,6 +79,187 @@ pub struct Encounter {
     pub favorite: bool,
     pub cleared: bool,
     pub boss_only_damage: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kill_projection: Option<KillProjection>,
+}
+
+impl Encounter {
+    // moves non-boss damage into or out of every stat it is tracked for,
+    // a log is stored in the view it was taken in
+    pub fn apply_damage_view(&mut self, boss_only: bool) {
+        // cast logs keep every hit, drop the non-boss ones from the boss only view
+        if boss_only {
+            for skill in self
+                .entities
+                .values_mut()
+                .flat_map(|e| e.skills.values_mut())
+            {
+                skill.skill_cast_log.retain_mut(|cast| {
+                    let hits = cast.hits.len();
+                    cast.hits.retain(|hit| hit.in_view(true));
+                    hits == 0 || !cast.hits.is_empty()
+                });
+                skill.update_cast_stats(true);
+            }
+        }
+        if self.boss_only_damage == boss_only {
+            return;
+        }
+        let sign = if boss_only { -1 } else { 1 };
+        let duration_seconds = (self.duration / 1000).max(1);
+
+        for entity in self.entities.values_mut() {
+            let non_boss = entity.damage_stats.non_boss.clone();
+            let damage_stats = &mut entity.damage_stats;
+            damage_stats.damage_dealt += sign * non_boss.damage;
+            damage_stats.crit_damage += sign * non_boss.crit_damage;
+            damage_stats.back_attack_damage += sign * non_boss.back_attack_damage;
+            damage_stats.front_attack_damage += sign * non_boss.front_attack_damage;
+            damage_stats.buffed_by_support += sign * non_boss.buffed_by_support;
+            damage_stats.buffed_by_identity += sign * non_boss.buffed_by_identity;
+            damage_stats.debuffed_by_support += sign * non_boss.debuffed_by_support;
+            add_damage_map(&mut damage_stats.buffed_by, &non_boss.buffed_by, sign);
+            add_damage_map(&mut damage_stats.debuffed_by, &non_boss.debuffed_by, sign);
+            damage_stats.rdps_damage_received += sign * non_boss.rdps_damage_received;
+            damage_stats.rdps_damage_received_support +=
+                sign * non_boss.rdps_damage_received_support;
+            damage_stats.rdps_damage_given += sign * non_boss.rdps_damage_given;
+            for (source, by_category) in non_boss.rdps_received_from.iter() {
+                add_damage_map(
+                    damage_stats
+                        .rdps_received_from
+                        .entry(source.clone())
+                        .or_default(),
+                    by_category,
+                    sign,
+                );
+            }
+            add_damage_map(
+                &mut damage_stats.rdps_received_by_category,
+                &non_boss.rdps_received_by_category,
+                sign,
+            );
+            add_damage_map(
+                &mut damage_stats.rdps_received_by_buff,
+                &non_boss.rdps_received_by_buff,
+                sign,
+            );
+            add_damage_map(
+                &mut damage_stats.damage_by_attribute,
+                &non_boss.damage_by_attribute,
+                sign,
+            );
+            add_damage_map(
+                &mut damage_stats.damage_by_type,
+                &non_boss.damage_by_type,
+                sign,
+            );
+            damage_stats
+                .wasted_damage
+                .add(&non_boss.wasted_damage, sign);
+            damage_stats.dps = damage_stats.damage_dealt / duration_seconds;
+
+            entity.skill_stats.hits += sign * non_boss.hits;
+            entity.skill_stats.crits += sign * non_boss.crits;
+            entity.skill_stats.back_attacks += sign * non_boss.back_attacks;
+            entity.skill_stats.front_attacks += sign * non_boss.front_attacks;
+
+            for skill in entity.skills.values_mut() {
+                let non_boss = &skill.non_boss;
+                skill.total_damage += sign * non_boss.damage;
+                skill.hits += sign * non_boss.hits;
+                skill.crits += sign * non_boss.crits;
+                skill.crit_damage += sign * non_boss.crit_damage;
+                skill.back_attacks += sign * non_boss.back_attacks;
+                skill.back_attack_damage += sign * non_boss.back_attack_damage;
+                skill.front_attacks += sign * non_boss.front_attacks;
+                skill.front_attack_damage += sign * non_boss.front_attack_damage;
+                skill.buffed_by_support += sign * non_boss.buffed_by_support;
+                skill.buffed_by_identity += sign * non_boss.buffed_by_identity;
+                skill.debuffed_by_support += sign * non_boss.debuffed_by_support;
+                add_damage_map(&mut skill.buffed_by, &non_boss.buffed_by, sign);
+                add_damage_map(&mut skill.debuffed_by, &non_boss.debuffed_by, sign);
+                skill.rdps_damage_received += sign * non_boss.rdps_damage_received;
+                skill.rdps_damage_received_support += sign * non_boss.rdps_damage_received_support;
+                add_damage_map(
+                    &mut skill.rdps_received_by_category,
+                    &non_boss.rdps_received_by_category,
+                    sign,
+                );
+                add_damage_map(
+                    &mut skill.rdps_received_by_buff,
+                    &non_boss.rdps_received_by_buff,
+                    sign,
+                );
+                add_damage_map(
+                    &mut skill.damage_by_attribute,
+                    &non_boss.damage_by_attribute,
+                    sign,
+                );
+                add_damage_map(&mut skill.damage_by_type, &non_boss.damage_by_type, sign);
+                skill.wasted_damage.add(&non_boss.wasted_damage, sign);
+                skill.primary_damage += sign * non_boss.primary_damage;
+                skill.cleave_damage += sign * non_boss.cleave_damage;
+                if non_boss.boss_max_damage > 0 || non_boss.max_damage > 0 {
+                    skill.max_damage = if boss_only {
+                        non_boss.boss_max_damage
+                    } else {
+                        non_boss.boss_max_damage.max(non_boss.max_damage)
+                    };
+                }
+                for (key, non_boss_source) in non_boss.sub_sources.iter() {
+                    let boss_max_damage = non_boss
+                        .sub_source_boss_max_damage
+                        .get(key)
+                        .cloned()
+                        .unwrap_or_default();
+                    let empty = SkillSubSource {
+                        source_type: non_boss_source.source_type.clone(),
+                        id: non_boss_source.id,
+                        name: non_boss_source.name.clone(),
+                        ..Default::default()
+                    };
+                    let sub_source = skill.sub_sources.entry(key.clone()).or_insert(empty);
+                    sub_source.damage += sign * non_boss_source.damage;
+                    sub_source.hits += sign * non_boss_source.hits;
+                    sub_source.crits += sign * non_boss_source.crits;
+                    sub_source.max_damage = if boss_only {
+                        boss_max_damage
+                    } else {
+                        boss_max_damage.max(non_boss_source.max_damage)
+                    };
+                }
+                skill
+                    .sub_sources
+                    .retain(|_, sub_source| sub_source.hits > 0);
+                skill.update_cast_stats(boss_only);
+                skill.dps = skill.total_damage / duration_seconds;
+            }
+        }
+
+        let players = self
+            .entities
+            .values()
+            .filter(|e| e.entity_type == EntityType::PLAYER);
+        self.encounter_damage_stats.total_damage_dealt =
+            players.clone().map(|e| e.damage_stats.damage_dealt).sum();
+        self.encounter_damage_stats.top_damage_dealt = players
+            .map(|e| e.damage_stats.damage_dealt)
+            .max()
+            .unwrap_or_default();
+        self.encounter_damage_stats.dps =
+            self.encounter_damage_stats.total_damage_dealt / duration_seconds;
+        self.boss_only_damage = boss_only;
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct KillProjection {
+    // boss hp lost per second, smoothed
+    pub hp_per_second: f64,
+    pub time_to_kill: i64,
+    pub time_to_next_bar: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Clone, Default)]
@@ -97,11 +282,16 @@ pub struct EncounterDamageStats {
     pub max_stagger: i32,
     #[serde(skip)]
     pub stagger_start: i64,
+    // (start, end) of hits the boss was invincible to, relative to fight start
+    #[serde(skip)]
+    pub boss_invincible_log: Vec<(i32, i32)>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub misc: Option<EncounterMisc>,
     pub boss_hp_log: HashMap<String, Vec<BossHpLog>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stagger_stats: Option<StaggerStats>,
+    pub status_effect_log: HashMap<String, StatusEffectLog>,
+    pub esther_log: Vec<EstherUse>,
 }
 
 #[derive(Debug, Serialize, Clone, Default)]
@@ -184,11 +374,195 @@ pub struct Skill {
     pub rdps_damage_received: i64,
     pub rdps_damage_received_support: i64,
     pub rdps_damage_given: i64,
+    pub rdps_received_by_category: HashMap<String, i64>,
+    pub rdps_received_by_buff: HashMap<u32, i64>,
     pub skill_cast_log: Vec<SkillCast>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cooldown_stats: Option<SkillCooldownStats>,
+    pub wasted_damage: WastedDamage,
+    pub damage_by_attribute: HashMap<String, i64>,
+    pub damage_by_type: HashMap<String, i64>,
+    // damage to the boss or single targets vs extra targets of multi-target hits
+    pub primary_damage: i64,
+    pub cleave_damage: i64,
+    // targets hit -> casts
+    pub target_distribution: HashMap<i32, i64>,
+    pub non_boss: NonBossDamage,
+    // summon, projectile and trap damage under this skill
+    pub sub_sources: HashMap<String, SkillSubSource>,
+    // ids of the same skill family grouped into this skill
+    pub variant_ids: Vec<u32>,
     #[serde(skip)]
     pub last_timestamp: i64,
 }
 
+impl Skill {
+    // per cast stats from the hits in the cast log that belong to the view
+    pub fn update_cast_stats(&mut self, boss_only: bool) {
+        let casts = self
+            .skill_cast_log
+            .iter()
+            .map(|cast| cast.hits.iter().filter(|hit| hit.in_view(boss_only)))
+            .filter(|hits| hits.clone().next().is_some());
+        self.max_damage_cast = casts
+            .clone()
+            .map(|hits| hits.map(|hit| hit.damage).sum::<i64>())
+            .max()
+            .unwrap_or_default();
+        self.target_distribution = HashMap::new();
+        for hits in casts {
+            if let Some(targets) = hits.map(|hit| hit.target_count).max() {
+                *self.target_distribution.entry(targets).or_default() += 1;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SkillSubSource {
+    pub source_type: String,
+    // npc id for summons, skill effect id otherwise
+    pub id: u32,
+    pub name: String,
+    pub damage: i64,
+    pub hits: i64,
+    pub crits: i64,
+    pub max_damage: i64,
+}
+
+impl SkillSubSource {
+    pub fn add_hit(&mut self, damage: i64, crit: bool) {
+        self.damage += damage;
+        self.hits += 1;
+        if crit {
+            self.crits += 1;
+        }
+        if damage > self.max_damage {
+            self.max_damage = damage;
+        }
+    }
+}
+
+// stats recomputed from skill hits within a time window
+#[derive(Debug, Default, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowStats {
+    pub damage: i64,
+    pub dps: i64,
+    pub hits: i64,
+    pub crits: i64,
+    pub crit_rate: f64,
+    pub back_attack_rate: f64,
+    pub front_attack_rate: f64,
+    pub buffed_by: HashMap<u32, i64>,
+    pub debuffed_by: HashMap<u32, i64>,
+    pub rdps_damage_received: i64,
+    pub rdps_damage_received_support: i64,
+    #[serde(skip)]
+    back_attacks: i64,
+    #[serde(skip)]
+    front_attacks: i64,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub skills: HashMap<u32, WindowStats>,
+}
+
+impl WindowStats {
+    pub fn add_hit(&mut self, hit: &SkillHit) {
+        self.damage += hit.damage;
+        self.hits += 1;
+        if hit.crit {
+            self.crits += 1;
+        }
+        if hit.back_attack {
+            self.back_attacks += 1;
+        }
+        if hit.front_attack {
+            self.front_attacks += 1;
+        }
+        for buff_id in hit.buffed_by.iter() {
+            self.buffed_by
+                .entry(*buff_id)
+                .and_modify(|e| *e += hit.damage)
+                .or_insert(hit.damage);
+        }
+        for debuff_id in hit.debuffed_by.iter() {
+            self.debuffed_by
+                .entry(*debuff_id)
+                .and_modify(|e| *e += hit.damage)
+                .or_insert(hit.damage);
+        }
+        self.rdps_damage_received += hit.rdps_damage_received;
+        self.rdps_damage_received_support += hit.rdps_damage_received_support;
+    }
+
+    pub fn finish(&mut self, duration_seconds: f64) {
+        self.dps = (self.damage as f64 / duration_seconds) as i64;
+        if self.hits > 0 {
+            self.crit_rate = self.crits as f64 / self.hits as f64;
+            self.back_attack_rate = self.back_attacks as f64 / self.hits as f64;
+            self.front_attack_rate = self.front_attacks as f64 / self.hits as f64;
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WastedDamage {
+    pub overkill: i64,
+    pub invincible_damage: i64,
+    pub invincible_hits: i64,
+    pub immune_damage: i64,
+    pub immune_hits: i64,
+    pub misses: i64,
+    pub dodges: i64,
+}
+
+impl WastedDamage {
+    pub fn add_hit(&mut self, hit_flag: &HitFlag, damage: i64, overkill: i64) {
+        self.overkill += overkill;
+        match hit_flag {
+            HitFlag::INVINCIBLE => {
+                self.invincible_damage += damage;
+                self.invincible_hits += 1;
+            }
+            HitFlag::IMMUNE | HitFlag::IMMUNE_SILENCED => {
+                self.immune_damage += damage;
+                self.immune_hits += 1;
+            }
+            HitFlag::MISS => self.misses += 1,
+            HitFlag::DODGE => self.dodges += 1,
+            _ => {}
+        }
+    }
+
+    pub fn add(&mut self, other: &WastedDamage, sign: i64) {
+        self.overkill += sign * other.overkill;
+        self.invincible_damage += sign * other.invincible_damage;
+        self.invincible_hits += sign * other.invincible_hits;
+        self.immune_damage += sign * other.immune_damage;
+        self.immune_hits += sign * other.immune_hits;
+        self.misses += sign * other.misses;
+        self.dodges += sign * other.dodges;
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SkillCooldownStats {
+    // effective cooldown in ms
+    pub cooldown: i64,
+    // "data" when taken from skill data with tripods, gems and swiftness applied,
+    // "observed" when it is the shortest gap between two casts
+    pub source: String,
+    pub expected_casts: i64,
+    pub casts: i64,
+    // time the skill was off cooldown but not cast
+    pub idle_time: i64,
+    // average wait between the skill coming off cooldown and the next cast
+    pub average_delay: f64,
+}
+
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase", default)]
 pub struct TripodData {
@@ -260,6 +634,142 @@ pub struct DamageStats {
     pub rdps_damage_received: i64,
     pub rdps_damage_received_support: i64,
     pub rdps_damage_given: i64,
+    // buffer name -> rdps category -> damage received
+    pub rdps_received_from: HashMap<String, HashMap<String, i64>>,
+    pub rdps_received_by_category: HashMap<String, i64>,
+    pub rdps_received_by_buff: HashMap<u32, i64>,
+    pub death_log: Vec<DeathRecap>,
+    // status effect id -> fraction of the fight it was active
+    pub status_effect_uptime: HashMap<u32, f64>,
+    pub healing_done: i64,
+    pub healing_received: i64,
+    pub healing_done_by: HashMap<u32, i64>,
+    pub healing_received_by: HashMap<u32, i64>,
+    // target name -> healing done to them
+    pub healing_done_to: HashMap<String, i64>,
+    // source name -> healing received from them
+    pub healing_received_from: HashMap<String, i64>,
+    // part of a heal's amount that never showed up as restored hp
+    pub overheal: i64,
+    pub battle_item_log: Vec<BattleItemUse>,
+    pub awakening_log: Vec<AwakeningUse>,
+    pub counter_log: Vec<CounterAttempt>,
+    pub wasted_damage: WastedDamage,
+    pub damage_by_attribute: HashMap<String, i64>,
+    pub damage_by_type: HashMap<String, i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub downtime: Option<DowntimeStats>,
+    pub non_boss: NonBossDamage,
+    // seconds since fight start, percentage of the gauge spent
+    pub identity_spends: Vec<(i32, f32)>,
+    // fraction of the fight the player's own identity buffs were active
+    pub identity_uptime: f64,
+}
+
+// damage to targets other than the boss, always tracked so a log can show both views
+// logs taken with boss only damage keep it out of every other stat, otherwise it is
+// included and gets subtracted for the boss only view
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NonBossDamage {
+    pub damage: i64,
+    pub hits: i64,
+    pub crits: i64,
+    pub crit_damage: i64,
+    pub back_attacks: i64,
+    pub back_attack_damage: i64,
+    pub front_attacks: i64,
+    pub front_attack_damage: i64,
+    pub buffed_by_support: i64,
+    pub buffed_by_identity: i64,
+    pub debuffed_by_support: i64,
+    pub buffed_by: HashMap<u32, i64>,
+    pub debuffed_by: HashMap<u32, i64>,
+    pub rdps_damage_received: i64,
+    pub rdps_damage_received_support: i64,
+    pub rdps_damage_given: i64,
+    pub rdps_received_from: HashMap<String, HashMap<String, i64>>,
+    pub rdps_received_by_category: HashMap<String, i64>,
+    pub rdps_received_by_buff: HashMap<u32, i64>,
+    pub damage_by_attribute: HashMap<String, i64>,
+    pub damage_by_type: HashMap<String, i64>,
+    pub wasted_damage: WastedDamage,
+    pub primary_damage: i64,
+    pub cleave_damage: i64,
+    pub max_damage: i64,
+    // largest boss hit, so max damage can be restored for the boss only view
+    pub boss_max_damage: i64,
+    pub sub_sources: HashMap<String, SkillSubSource>,
+    pub sub_source_boss_max_damage: HashMap<String, i64>,
+}
+
+impl NonBossDamage {
+    // support flags are (buffed by support, buffed by identity, debuffed by support)
+    pub fn add_hit(&mut self, hit: &SkillHit, support_flags: (bool, bool, bool)) {
+        let damage = hit.damage;
+        self.damage += damage;
+        self.hits += 1;
+        if hit.crit {
+            self.crits += 1;
+            self.crit_damage += damage;
+        }
+        if hit.back_attack {
+            self.back_attacks += 1;
+            self.back_attack_damage += damage;
+        }
+        if hit.front_attack {
+            self.front_attacks += 1;
+            self.front_attack_damage += damage;
+        }
+        let (buffed_by_support, buffed_by_identity, debuffed_by_support) = support_flags;
+        if buffed_by_support {
+            self.buffed_by_support += damage;
+        }
+        if buffed_by_identity {
+            self.buffed_by_identity += damage;
+        }
+        if debuffed_by_support {
+            self.debuffed_by_support += damage;
+        }
+        for buff_id in hit.buffed_by.iter() {
+            *self.buffed_by.entry(*buff_id).or_default() += damage;
+        }
+        for debuff_id in hit.debuffed_by.iter() {
+            *self.debuffed_by.entry(*debuff_id).or_default() += damage;
+        }
+    }
+}
+
+fn add_damage_map<K: Eq + Hash + Clone>(
+    map: &mut HashMap<K, i64>,
+    other: &HashMap<K, i64>,
+    sign: i64,
+) {
+    for (key, damage) in other.iter() {
+        *map.entry(key.clone()).or_default() += sign * damage;
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DowntimeStats {
+    pub active_time: i64,
+    pub active_time_percent: f64,
+    // dps over active time only
+    pub active_dps: i64,
+    pub dead_time: i64,
+    pub invulnerable_time: i64,
+    pub other_time: i64,
+    pub gaps: Vec<DowntimeGap>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DowntimeGap {
+    pub start: i64,
+    pub end: i64,
+    // dead, invulnerable or other
+    pub reason: String,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
@@ -295,6 +805,120 @@ pub struct SkillHit {
     pub debuffed_by: Vec<u32>,
     pub rdps_damage_received: i64,
     pub rdps_damage_received_support: i64,
+    #[serde(default)]
+    pub target_count: i32,
+    // hit on a target other than the boss
+    #[serde(default)]
+    pub non_boss: bool,
+}
+
+impl SkillHit {
+    pub fn in_view(&self, boss_only: bool) -> bool {
+        !(boss_only && self.non_boss)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DeathRecap {
+    // relative to fight start
+    pub timestamp: i64,
+    pub hits: Vec<IncomingHit>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct IncomingHit {
+    pub timestamp: i64,
+    pub source: String,
+    pub skill_id: u32,
+    pub skill_name: String,
+    pub damage: i64,
+    pub shield_absorbed: u64,
+    pub hp_after: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BattleItemUse {
+    pub timestamp: i64,
+    pub item_id: u32,
+    pub name: String,
+    pub item_type: String,
+    pub damage: i64,
+    pub hits: i64,
+    pub effects_applied: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AwakeningUse {
+    pub timestamp: i64,
+    pub skill_id: u32,
+    pub boss_name: String,
+    pub boss_hp_percent: f32,
+    // starts at 1 and advances on every boss kill in the encounter
+    pub phase: u32,
+    pub hyper_awakening: bool,
+    pub damage: i64,
+    pub hits: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CounterAttempt {
+    // relative to fight start
+    pub timestamp: i64,
+    pub skill_id: u32,
+    pub target: String,
+    pub success: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UtilityStats {
+    pub counters: i64,
+    pub failed_counters: i64,
+    // boss hits with skills that have a destruction or weak point level
+    pub destruction_hits: i64,
+    pub weak_point_hits: i64,
+    pub destruction_by_skill: HashMap<u32, i64>,
+    pub weak_point_by_skill: HashMap<u32, i64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EstherUse {
+    pub timestamp: i64,
+    pub name: String,
+    pub triggered_by: String,
+    pub boss_name: String,
+    pub boss_hp_percent: f32,
+    pub boss_hp_bars: Option<u32>,
+    pub damage: i64,
+    pub hits: i64,
+    // boss stagger dealt right after the esther's hits
+    pub stagger: i64,
+    pub shield: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EstherSummary {
+    pub uses: i64,
+    pub damage: i64,
+    pub stagger: i64,
+    pub shield: u64,
+    pub triggered_by: HashMap<String, i64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AwakeningSummary {
+    pub awakening_casts: i64,
+    pub hyper_awakening_casts: i64,
+    pub awakening_damage: i64,
+    pub average_boss_hp_percent: f32,
 }
 
 #[derive(Debug)]
@@ -326,6 +950,11 @@ pub struct Stagger {
 
 pub type IdentityLog = Vec<(i64, (u32, u32, u32))>;
 
+// status effect id -> (start, end) relative to fight start
+pub type StatusEffectLog = HashMap<u32, Vec<(i32, i32)>>;
+// buffer -> receiver -> rdps category -> damage
+pub type RdpsMatrix = HashMap<String, HashMap<String, HashMap<String, i64>>>;
+
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct IdentityArcanist {
@@ -360,6 +989,93 @@ pub struct StaggerStats {
     pub log: Vec<(i32, f32)>,
 }
 
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SupportScorecard {
+    pub class_id: u32,
+    pub brand_uptime: f64,
+    pub ap_buff_uptime: f64,
+    pub identity_uptime: f64,
+    pub shields_given: u64,
+    pub shields_absorbed: u64,
+    // share of party damage dealt while the buff was active
+    pub brand_damage_share: f64,
+    pub ap_buff_damage_share: f64,
+    pub identity_damage_share: f64,
+    pub buff_damage_share: HashMap<u32, f64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BurstAlignment {
+    pub party: i32,
+    // share of big skill damage that landed inside a party burst window
+    pub score: f64,
+    pub big_casts: i32,
+    pub aligned_casts: i32,
+    pub big_cast_damage: i64,
+    pub aligned_damage: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PlayerRole {
+    // "support" or "dps"
+    pub role: String,
+    // main class engraving name from the stats api, or the stance for deadeye and
+    // gunslinger, none otherwise
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PartyComposition {
+    pub supports: i32,
+    pub dps: i32,
+    pub builds: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RdpsInvalidReason {
+    SyncDisabled,
+    InvalidZone,
+    InvalidDifficulty,
+    MissingStats,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RdpsInvalidation {
+    pub reasons: Vec<RdpsInvalidReason>,
+    // player -> time relative to fight start their stats were first missing
+    pub missing_stats: HashMap<String, i64>,
+}
+
+impl RdpsInvalidation {
+    // returns true if anything new was recorded
+    pub fn add(
+        &mut self,
+        reason: RdpsInvalidReason,
+        player: Option<&str>,
+        timestamp: i64,
+    ) -> bool {
+        let mut changed = false;
+        if !self.reasons.contains(&reason) {
+            self.reasons.push(reason);
+            changed = true;
+        }
+        if let Some(player) = player {
+            if let Entry::Vacant(entry) = self.missing_stats.entry(player.to_string()) {
+                entry.insert(timestamp);
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase", default)]
 #[serde_as]
@@ -381,9 +1097,39 @@ pub struct EncounterMisc {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rdps_message: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub rdps_invalidation: Option<RdpsInvalidation>,
+    // rdps from the local player tracker's stats, no engravings or gems
+    pub rdps_local: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub ntp_fight_start: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub manual_save: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub party_status_effect_uptime: Option<HashMap<i32, HashMap<u32, f64>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub support_scorecards: Option<HashMap<String, SupportScorecard>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub awakening_summary: Option<HashMap<String, AwakeningSummary>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utility_stats: Option<HashMap<String, UtilityStats>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kill_projection_accuracy: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub esther_log: Option<Vec<EstherUse>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub esther_summary: Option<HashMap<String, EstherSummary>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rdps_matrix: Option<RdpsMatrix>,
+    // relative ms, keyed by party index
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub burst_windows: Option<HashMap<i32, Vec<(i32, i32)>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub burst_alignment: Option<HashMap<String, BurstAlignment>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub player_roles: Option<HashMap<String, PlayerRole>>,
+    // keyed by party index
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub party_composition: Option<HashMap<i32, PartyComposition>>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -410,6 +1156,52 @@ pub struct Npc {
     pub npc_type: String,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkillFamilyKind {
+    // tripod or stance variant, a cast of the base skill
+    Variant,
+    // later stages and summons of an awakening
+    Awakening,
+    // separate skill whose damage is shown under the base skill
+    Sub,
+}
+
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(default)]
+pub struct SkillFamily {
+    pub name: String,
+    pub base: u32,
+    pub variants: Vec<u32>,
+    pub awakening: Vec<u32>,
+    pub sub: Vec<u32>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct IdentityGaugeData {
+    pub class_id: u32,
+    pub class: String,
+    pub max: u32,
+    // "gauge", "bubbles" (gauge2 is the bubble count) or "cards" (gauge2 and gauge3 are card ids)
+    pub kind: String,
+    pub bubbles: u32,
+    // draws of these cards aren't counted
+    pub ignored_cards: Vec<u32>,
+}
+
+impl Default for IdentityGaugeData {
+    fn default() -> Self {
+        Self {
+            class_id: 0,
+            class: String::new(),
+            max: 10_000,
+            kind: "gauge".to_string(),
+            bubbles: 0,
+            ignored_cards: Vec::new(),
+        }
+    }
+}
+
 #[derive(Debug, Default, Deserialize, Clone)]
 pub struct Esther {
     pub name: String,
@@ -438,6 +1230,17 @@ pub struct SkillData {
     pub summon_source_skill: Option<Vec<u32>>,
     #[serde(alias = "sourceskill", alias = "sourceSkill")]
     pub source_skill: Option<Vec<u32>>,
+    // base cooldown in seconds
+    #[serde(alias = "cooldowntime", alias = "coolTime")]
+    pub cooldown: Option<f32>,
+    // part break level
+    #[serde(alias = "destructionlevel", alias = "destructionLevel")]
+    pub destruction: Option<u8>,
+    // weak point level
+    #[serde(alias = "weakpoint", alias = "weakPoint")]
+    pub weak_point: Option<u8>,
+    #[serde(alias = "hyperawakening", alias = "hyperAwakening")]
+    pub hyper_awakening: Option<bool>,
 }
 
 #[derive(Debug, Default, Deserialize, Clone)]
@@ -644,6 +1447,8 @@ pub struct EncounterPreview {
     pub my_dps: i64,
     pub favorite: bool,
     pub cleared: bool,
+    pub my_build: Option<String>,
+    pub my_party_supports: Option<i32>,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
@@ -663,6 +1468,8 @@ pub struct SearchFilter {
     pub favorite: bool,
     pub difficulty: String,
     pub boss_only_damage: bool,
+    pub build: String,
+    pub party_supports: Option<i32>,
     pub sort: String,
     pub order: u8,
 }
@@ -681,6 +1488,7 @@ pub struct Settings {
 #[serde(rename_all = "camelCase", default)]
 pub struct GeneralSettings {
     pub low_performance_mode: bool,
+    pub local_rdps: bool,
     #[serde(default = "default_true")]
     pub show_names: bool,
     pub show_gear_score: bool,
@@ -967,6 +1775,7 @@ pub struct RdpsSelfRates {
 #[derive(Debug, Default, Clone)]
 pub struct RdpsBuffData {
     pub caster: String,
+    pub buff_id: u32,
     pub rate: f64,
 }
 
@@ -1042,6 +1851,44 @@ lazy_static! {
         let json_str = include_str!("../../meter-data/Esther.json");
         serde_json::from_str(json_str).unwrap()
     };
+    pub static ref IDENTITY_GAUGE_DATA: HashMap<u32, IdentityGaugeData> = {
+        let json_str = include_str!("../../meter-data/IdentityGauge.json");
+        let gauges: Vec<IdentityGaugeData> = serde_json::from_str(json_str).unwrap();
+        gauges.into_iter().map(|gauge| (gauge.class_id, gauge)).collect()
+    };
+    // skill id -> base skill id of its family and how the skill relates to it
+    pub static ref SKILL_FAMILY_DATA: HashMap<u32, (u32, SkillFamilyKind)> = {
+        let json_str = include_str!("../../meter-data/SkillFamily.json");
+        let families: Vec<SkillFamily> = serde_json::from_str(json_str).unwrap();
+        let mut skill_families = HashMap::new();
+        for family in families {
+            // same-name skills outside the base's id block are different skills
+            for skill_id in family.variants.iter() {
+                if skill_id / 100 == family.base / 100 {
+                    skill_families.insert(*skill_id, (family.base, SkillFamilyKind::Variant));
+                }
+            }
+            for skill_id in family.awakening.iter() {
+                skill_families.insert(*skill_id, (family.base, SkillFamilyKind::Awakening));
+            }
+            for skill_id in family.sub.iter() {
+                skill_families.insert(*skill_id, (family.base, SkillFamilyKind::Sub));
+            }
+        }
+        skill_families
+    };
+    // skill id -> tripod keys that turn the skill into a counter
+    pub static ref COUNTER_TRIPOD_DATA: HashMap<u32, Vec<u8>> = {
+        let mut counter_tripods: HashMap<u32, Vec<u8>> = HashMap::new();
+        for (skill_id, feature) in SKILL_FEATURE_DATA.iter() {
+            for (key, tripod) in feature.tripods.iter() {
+                if tripod.name.starts_with("Counter") {
+                    counter_tripods.entry(*skill_id).or_default().push(*key);
+                }
+            }
+        }
+        counter_tripods
+    };
     pub static ref VALID_ZONES: HashSet<u32> = {
         let valid_zones = [
             30801, 30802, 30803, 30804, 30805, 30806, 30807, 30835, 37001, 37002, 37003, 37011,
@@ -1057,6 +1904,11 @@ lazy_static! {
 
         valid_zones.iter().cloned().collect()
     };
+    // shared with the frontend
+    pub static ref BOSS_HP_BARS: HashMap<String, u32> = {
+        let json_str = include_str!("../../meter-data/BossHpBars.json");
+        serde_json::from_str(json_str).unwrap()
+    };
     pub static ref STAT_TYPE_MAP: HashMap<&'static str, u32> = {
         let mut map = HashMap::new();
         map.insert("none", 0);
@@ -390,26 +390,98 @@ pub fn calculate_tripod_data(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn apply_rdps(
     damage_owner: &mut EncounterEntity,
     source_entity: Option<&mut EncounterEntity>,
     skill_id: u32,
     delta: f64,
     skill_hit: &mut SkillHit,
+    category: &str,
+    buff_id: u32,
+    non_boss: bool,
+    non_boss_only: bool,
 ) {
     let delta = delta.round() as i64;
     let skill = damage_owner.skills.get_mut(&skill_id).unwrap();
+    let support_source = source_entity
+        .as_ref()
+        .map_or(false, |source| is_support_class_id(source.class_id));
     if let Some(source) = source_entity {
-        source.damage_stats.rdps_damage_given += delta;
+        if non_boss {
+            source.damage_stats.non_boss.rdps_damage_given += delta;
+            *damage_owner
+                .damage_stats
+                .non_boss
+                .rdps_received_from
+                .entry(source.name.clone())
+                .or_default()
+                .entry(category.to_string())
+                .or_default() += delta;
+        }
+        // boss only damage keeps non-boss hits out of the main rdps totals
+        if !non_boss_only {
+            source.damage_stats.rdps_damage_given += delta;
+            damage_owner
+                .damage_stats
+                .rdps_received_from
+                .entry(source.name.clone())
+                .or_default()
+                .entry(category.to_string())
+                .and_modify(|e| *e += delta)
+                .or_insert(delta);
+        }
+    }
 
-        if is_support_class_id(source.class_id) {
-            damage_owner.damage_stats.rdps_damage_received_support += delta;
-            skill.rdps_damage_received_support += delta;
-            skill_hit.rdps_damage_received_support += delta;
+    if non_boss {
+        if support_source {
+            damage_owner
+                .damage_stats
+                .non_boss
+                .rdps_damage_received_support += delta;
+            skill.non_boss.rdps_damage_received_support += delta;
         }
+        damage_owner.damage_stats.non_boss.rdps_damage_received += delta;
+        skill.non_boss.rdps_damage_received += delta;
+        for non_boss in [&mut damage_owner.damage_stats.non_boss, &mut skill.non_boss] {
+            *non_boss
+                .rdps_received_by_category
+                .entry(category.to_string())
+                .or_default() += delta;
+            *non_boss.rdps_received_by_buff.entry(buff_id).or_default() += delta;
+        }
+    }
+    if support_source {
+        skill_hit.rdps_damage_received_support += delta;
+    }
+    skill_hit.rdps_damage_received += delta;
+    if non_boss_only {
+        return;
     }
 
+    if support_source {
+        damage_owner.damage_stats.rdps_damage_received_support += delta;
+        skill.rdps_damage_received_support += delta;
+    }
     damage_owner.damage_stats.rdps_damage_received += delta;
     skill.rdps_damage_received += delta;
-    skill_hit.rdps_damage_received += delta;
+
+    for by_category in [
+        &mut damage_owner.damage_stats.rdps_received_by_category,
+        &mut skill.rdps_received_by_category,
+    ] {
+        by_category
+            .entry(category.to_string())
+            .and_modify(|e| *e += delta)
+            .or_insert(delta);
+    }
+    for by_buff in [
+        &mut damage_owner.damage_stats.rdps_received_by_buff,
+        &mut skill.rdps_received_by_buff,
+    ] {
+        by_buff
+            .entry(buff_id)
+            .and_modify(|e| *e += delta)
+            .or_insert(delta);
+    }
 }
@@ -1,7 +1,7 @@
 use crate::parser::debug_print;
 use crate::parser::encounter_state::EncounterState;
 use crate::parser::entity_tracker::Entity;
-use crate::parser::models::EntityType;
+use crate::parser::models::{EntityType, RdpsInvalidReason, STAT_TYPE_MAP};
 use async_recursion::async_recursion;
 use hashbrown::HashMap;
 use log::{info, warn};
@@ -17,6 +17,8 @@ use std::time::Duration;
 use tauri::{Manager, Window, Wry};
 
 pub const API_URL: &str = "https://inspect.fau.dev";
+// todo
+const SYNC_ENABLED: bool = false;
 
 pub struct StatsApi {
     pub client_id: String,
@@ -31,6 +33,9 @@ pub struct StatsApi {
     region_file_path: String,
 
     pub region: String,
+
+    pub local_rdps: bool,
+    local_stats_cache: Cache<String, PlayerStats>,
 }
 
 impl StatsApi {
@@ -50,12 +55,16 @@ impl StatsApi {
             region_file_path,
 
             region: "".to_string(),
+
+            local_rdps: false,
+            local_stats_cache: Cache::builder().max_capacity(64).build(),
         }
     }
 
     pub fn sync(&mut self, player: &Entity, state: &EncounterState) {
-        // todo
-        return;
+        if !SYNC_ENABLED {
+            return;
+        }
         if state.encounter.fight_start > 0
             && state.encounter.last_combat_packet - state.encounter.fight_start > 1_000
         {
@@ -189,6 +198,10 @@ impl StatsApi {
     }
 
     pub fn get_stats(&mut self, state: &EncounterState) -> Option<Cache<String, PlayerStats>> {
+        if self.local_rdps {
+            return Some(self.local_stats_cache.clone());
+        }
+
         if !self.valid_difficulty(&state.raid_difficulty) {
             return None;
         }
@@ -196,6 +209,69 @@ impl StatsApi {
         Some(self.stats_cache.clone())
     }
 
+    pub fn rdps_invalid_reason(&self, difficulty: &str) -> Option<RdpsInvalidReason> {
+        if self.local_rdps {
+            None
+        } else if !SYNC_ENABLED {
+            Some(RdpsInvalidReason::SyncDisabled)
+        } else if !self.valid_zone {
+            Some(RdpsInvalidReason::InvalidZone)
+        } else if !self.valid_difficulty(difficulty) {
+            Some(RdpsInvalidReason::InvalidDifficulty)
+        } else {
+            None
+        }
+    }
+
+    // build stats from the player's own stat pairs instead of the inspect api
+    // only combat stats and attack power are sent to us, engravings, gems and elixirs
+    // aren't, so they're left empty and any self rates they'd add are missing
+    // refreshed every time the player is loaded in (init pc / new pc), not on gear swaps
+    pub fn update_local_stats(&mut self, player: &Entity) {
+        if !self.local_rdps || player.entity_type != EntityType::PLAYER {
+            return;
+        }
+
+        let stat = |name: &str| {
+            STAT_TYPE_MAP
+                .get(name)
+                .and_then(|stat_type| player.stats.get(&(*stat_type as u8)))
+                .map(|value| (*value).max(0) as u32)
+                .filter(|value| *value > 0)
+        };
+        // the total may already include the _x part, so that's only a fallback
+        let combat_stat = |name: &str| {
+            stat(name)
+                .or_else(|| stat(&format!("{}_x", name)))
+                .unwrap_or_default()
+        };
+        // rdps scales rates by attack power, without it the player gets no rdps
+        let atk_power = match stat("char_attack_dam") {
+            Some(atk_power) => atk_power,
+            None => {
+                self.local_stats_cache.invalidate(&player.name);
+                return;
+            }
+        };
+        let stats = Stats {
+            crit: combat_stat("criticalhit"),
+            spec: combat_stat("specialty"),
+            swift: combat_stat("rapidity"),
+            exp: combat_stat("mastery"),
+            atk_power,
+            add_dmg: 0,
+        };
+
+        self.local_stats_cache.insert(
+            player.name.clone(),
+            PlayerStats {
+                name: player.name.clone(),
+                stats,
+                ..Default::default()
+            },
+        );
+    }
+
     fn valid_difficulty(&self, difficulty: &str) -> bool {
         self.valid_zone
             && (difficulty == "Normal"
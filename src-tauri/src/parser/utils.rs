@@ -46,15 +46,101 @@ pub fn is_support_class_id(class_id: u32) -> bool {
     class_id == 105 || class_id == 204 || class_id == 602
 }
 
-pub fn is_battle_item(skill_effect_id: u32, _item_type: &str) -> bool {
+pub fn is_battle_item(skill_effect_id: u32, item_type: &str) -> bool {
     if let Some(item) = SKILL_EFFECT_DATA.get(&skill_effect_id) {
         if let Some(category) = item.item_category.as_ref() {
-            return category == "useup_battle_item_common_attack";
+            return category.strip_prefix("useup_battle_item_common_") == Some(item_type);
         }
     }
     false
 }
 
+// names from the damageattr enum
+pub fn get_damage_attribute_name(damage_attribute: Option<u8>) -> &'static str {
+    match damage_attribute {
+        Some(1) => "fire",
+        Some(2) => "ice",
+        Some(3) => "electricity",
+        Some(4) => "wind",
+        Some(5) => "earth",
+        Some(6) => "dark",
+        Some(7) => "holy",
+        _ => "none",
+    }
+}
+
+// names from the damagetype enum
+pub fn get_damage_type_name(damage_type: u8) -> &'static str {
+    match damage_type {
+        0 => "physics",
+        1 => "magic",
+        _ => "unknown",
+    }
+}
+
+// every class's awakening skills share this skill group
+const AWAKENING_SKILL_GROUP: i32 = 11_000_000;
+
+pub fn is_awakening_skill(skill_id: u32) -> bool {
+    SKILL_DATA
+        .get(&skill_id)
+        .and_then(|skill| skill.groups.as_ref())
+        .map_or(false, |groups| groups.contains(&AWAKENING_SKILL_GROUP))
+        || is_hyper_awakening_skill(skill_id)
+}
+
+pub fn is_hyper_awakening_skill(skill_id: u32) -> bool {
+    SKILL_DATA
+        .get(&skill_id)
+        .and_then(|skill| skill.hyper_awakening)
+        .unwrap_or(false)
+}
+
+pub fn get_tripod_keys(tripod_index: &TripodIndex) -> Vec<u8> {
+    let mut keys = vec![tripod_index.first];
+    if tripod_index.second != 0 {
+        keys.push(tripod_index.second + 3);
+    }
+    // third row should never be set if second is not set
+    if tripod_index.third != 0 {
+        keys.push(tripod_index.third + 6);
+    }
+    keys
+}
+
+pub fn has_counter_tripod(skill_id: u32, tripod_index: Option<TripodIndex>) -> bool {
+    match (COUNTER_TRIPOD_DATA.get(&skill_id), tripod_index) {
+        (Some(counter_keys), Some(tripod_index)) => get_tripod_keys(&tripod_index)
+            .iter()
+            .any(|key| counter_keys.contains(key)),
+        _ => false,
+    }
+}
+
+// battle item buffs share their id with the item's skill effect
+pub fn get_battle_item_type(skill_effect_id: u32) -> Option<&'static str> {
+    let item = SKILL_EFFECT_DATA.get(&skill_effect_id)?;
+    let category = item.item_category.as_ref()?;
+    if !category.starts_with("useup_battle_item") {
+        return None;
+    }
+
+    let name = item.item_name.clone().unwrap_or_default().to_lowercase();
+    let item_type = if category.ends_with("_attack") {
+        if name.contains("grenade") {
+            "grenade"
+        } else {
+            "bomb"
+        }
+    } else if category.ends_with("_recovery") || name.contains("potion") {
+        "potion"
+    } else {
+        "consumable"
+    };
+
+    Some(item_type)
+}
+
 pub fn get_status_effect_data(buff_id: u32, source_skill: Option<u32>) -> Option<StatusEffect> {
     let buff = SKILL_BUFF_DATA.get(&buff_id);
     if buff.is_none() || buff.unwrap().icon_show_type == "none" {
@@ -412,6 +498,27 @@ pub fn get_skill(skill_id: &u32) -> Option<SkillData> {
     SKILL_DATA.get(skill_id).cloned()
 }
 
+pub fn get_identity_gauge(class_id: u32) -> IdentityGaugeData {
+    IDENTITY_GAUGE_DATA
+        .get(&class_id)
+        .cloned()
+        .unwrap_or_default()
+}
+
+pub fn get_skill_family(skill_id: u32) -> u32 {
+    SKILL_FAMILY_DATA
+        .get(&skill_id)
+        .map_or(skill_id, |(base, _)| *base)
+}
+
+// sub skills add damage to their base skill but are not casts of it
+pub fn is_sub_skill(skill_id: u32) -> bool {
+    matches!(
+        SKILL_FAMILY_DATA.get(&skill_id),
+        Some((_, SkillFamilyKind::Sub))
+    )
+}
+
 pub fn get_class_from_id(class_id: &u32) -> String {
     let class = match class_id {
         0 => "",
@@ -679,6 +786,12 @@ pub fn check_tripod_level_change(before: Option<TripodLevel>, after: Option<Trip
 
 const WINDOW_MS: i64 = 5_000;
 const WINDOW_S: i64 = 5;
+// no damage for longer than this counts as downtime
+const DOWNTIME_GAP_MS: i64 = 3_000;
+// cooldown reduction per point of swiftness
+const SWIFTNESS_COOLDOWN_RATE: f64 = 0.000214748;
+// casts needed before the shortest gap between them is taken as the cooldown
+const OBSERVED_COOLDOWN_MIN_CASTS: usize = 3;
 
 #[allow(clippy::too_many_arguments)]
 pub fn insert_data(
@@ -686,6 +799,7 @@ pub fn insert_data(
     mut encounter: Encounter,
     prev_stagger: i32,
     damage_log: HashMap<String, Vec<(i64, i64)>>,
+    activity_log: HashMap<String, Vec<i64>>,
     identity_log: HashMap<String, IdentityLog>,
     cast_log: HashMap<String, HashMap<u32, Vec<i32>>>,
     boss_hp_log: HashMap<String, Vec<BossHpLog>>,
@@ -701,6 +815,11 @@ pub fn insert_data(
     rdps_valid: bool,
     manual: bool,
     skill_cast_log: HashMap<u64, HashMap<u32, BTreeMap<i64, SkillCast>>>,
+    mut status_effect_log: HashMap<String, StatusEffectLog>,
+    caster_status_effect_log: HashMap<String, HashMap<String, StatusEffectLog>>,
+    kill_projection_accuracy: Option<f64>,
+    rdps_invalidation: RdpsInvalidation,
+    rdps_local: bool,
 ) {
     let mut encounter_stmt = tx
         .prepare_cached(
@@ -720,8 +839,9 @@ pub fn insert_data(
         misc,
         version,
         boss_hp_log,
-        stagger_log
-    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+        stagger_log,
+        status_effect_log
+    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
         )
         .expect("failed to prepare encounter statement");
 
@@ -730,6 +850,86 @@ pub fn insert_data(
     encounter.encounter_damage_stats.dps =
         encounter.encounter_damage_stats.total_damage_dealt / duration_seconds;
 
+    // overlapping instances of the same effect only count once
+    for log in status_effect_log.values_mut() {
+        for intervals in log.values_mut() {
+            merge_intervals(intervals);
+        }
+    }
+
+    let party_status_effect_uptime = party_info
+        .iter()
+        .enumerate()
+        .map(|(index, party)| {
+            let mut uptime: HashMap<u32, f64> = HashMap::new();
+            for member in party {
+                if let Some(log) = status_effect_log.get(member) {
+                    for (buff_id, intervals) in log {
+                        let member_uptime = get_uptime(intervals, encounter.duration)
+                            / party.len() as f64;
+                        uptime
+                            .entry(*buff_id)
+                            .and_modify(|e| *e += member_uptime)
+                            .or_insert(member_uptime);
+                    }
+                }
+            }
+            (index as i32, uptime)
+        })
+        .collect::<HashMap<_, _>>();
+
+    let support_scorecards = get_support_scorecards(
+        &encounter,
+        &party_info,
+        &caster_status_effect_log,
+        &skill_cast_log,
+    );
+
+    let (burst_windows, burst_alignment) =
+        get_burst_alignment(&encounter, &party_info, &status_effect_log, &skill_cast_log);
+
+    let awakening_summary = get_awakening_summary(&encounter);
+    let utility_stats = get_utility_stats(&encounter);
+
+    let player_roles = get_player_roles(&encounter, &player_stats, &support_scorecards);
+    let party_composition = get_party_composition(&party_info, &player_roles);
+    let local_build = player_roles
+        .get(&encounter.local_player)
+        .and_then(|role| role.build.clone());
+    // without party info the local player's party is unknown
+    let local_party_supports = party_info
+        .iter()
+        .position(|party| party.contains(&encounter.local_player))
+        .and_then(|index| party_composition.get(&(index as i32)))
+        .map(|composition| composition.supports);
+
+    let mut rdps_matrix: RdpsMatrix = HashMap::new();
+    for receiver in encounter.entities.values() {
+        for (buffer, categories) in receiver.damage_stats.rdps_received_from.iter() {
+            rdps_matrix
+                .entry(buffer.clone())
+                .or_default()
+                .insert(receiver.name.clone(), categories.clone());
+        }
+    }
+
+    let esther_log = encounter.encounter_damage_stats.esther_log.clone();
+    let mut esther_summary: HashMap<String, EstherSummary> = HashMap::new();
+    for esther in esther_log.iter() {
+        let summary = esther_summary.entry(esther.name.clone()).or_default();
+        summary.uses += 1;
+        summary.damage += esther.damage;
+        summary.stagger += esther.stagger;
+        summary.shield += esther.shield;
+        if !esther.triggered_by.is_empty() {
+            summary
+                .triggered_by
+                .entry(esther.triggered_by.clone())
+                .and_modify(|e| *e += 1)
+                .or_insert(1);
+        }
+    }
+
     let misc: EncounterMisc = EncounterMisc {
         raid_clear: if raid_clear { Some(true) } else { None },
         party_info: if party_info.is_empty() {
@@ -751,8 +951,70 @@ pub fn insert_data(
         } else {
             Some("invalid_stats".to_string())
         },
+        rdps_invalidation: if rdps_invalidation.reasons.is_empty() {
+            None
+        } else {
+            Some(rdps_invalidation)
+        },
+        rdps_local,
         ntp_fight_start: Some(ntp_fight_start),
         manual_save: Some(manual),
+        party_status_effect_uptime: if party_status_effect_uptime.is_empty() {
+            None
+        } else {
+            Some(party_status_effect_uptime)
+        },
+        support_scorecards: if support_scorecards.is_empty() {
+            None
+        } else {
+            Some(support_scorecards)
+        },
+        awakening_summary: if awakening_summary.is_empty() {
+            None
+        } else {
+            Some(awakening_summary)
+        },
+        utility_stats: if utility_stats.is_empty() {
+            None
+        } else {
+            Some(utility_stats)
+        },
+        kill_projection_accuracy,
+        esther_log: if esther_log.is_empty() {
+            None
+        } else {
+            Some(esther_log)
+        },
+        esther_summary: if esther_summary.is_empty() {
+            None
+        } else {
+            Some(esther_summary)
+        },
+        rdps_matrix: if rdps_matrix.is_empty() {
+            None
+        } else {
+            Some(rdps_matrix)
+        },
+        burst_windows: if burst_windows.is_empty() {
+            None
+        } else {
+            Some(burst_windows)
+        },
+        burst_alignment: if burst_alignment.is_empty() {
+            None
+        } else {
+            Some(burst_alignment)
+        },
+        player_roles: if player_roles.is_empty() {
+            None
+        } else {
+            Some(player_roles)
+        },
+        party_composition: if party_composition.is_empty() {
+            None
+        } else {
+            Some(party_composition)
+        },
         ..Default::default()
     };
 
@@ -793,6 +1055,7 @@ pub fn insert_data(
     let compressed_buffs = compress_json(&encounter.encounter_damage_stats.buffs);
     let compressed_debuffs = compress_json(&encounter.encounter_damage_stats.debuffs);
     let compressed_shields = compress_json(&encounter.encounter_damage_stats.applied_shield_buffs);
+    let compressed_status_effect_log = compress_json(&status_effect_log);
 
     encounter_stmt
         .execute(params![
@@ -811,6 +1074,7 @@ pub fn insert_data(
             DB_VERSION,
             compressed_boss_hp,
             json!(stagger_stats),
+            compressed_status_effect_log,
         ])
         .expect("failed to insert encounter");
 
@@ -843,6 +1107,12 @@ pub fn insert_data(
 
     let fight_start = encounter.fight_start;
     let fight_end = encounter.last_combat_packet;
+    let forced_downtime = get_forced_downtime(
+        &activity_log,
+        fight_start,
+        fight_end,
+        &encounter.encounter_damage_stats.boss_invincible_log,
+    );
 
     for (_key, entity) in encounter.entities.iter_mut().filter(|(_, e)| {
         ((e.entity_type == EntityType::PLAYER && e.class_id != 0 && e.max_hp > 0)
@@ -871,10 +1141,56 @@ pub fn insert_data(
                 entity.damage_stats.dps_average =
                     calculate_average_dps(damage_log, fight_start_sec, fight_end_sec);
             }
+            // boss only damage leaves hits on adds out of the damage log,
+            // downtime goes by every hit so clearing adds isn't idle time
+            if let Some(activity) = activity_log.get(&entity.name) {
+                let deaths: Vec<i64> = entity
+                    .damage_stats
+                    .death_log
+                    .iter()
+                    .map(|death| death.timestamp)
+                    .collect();
+                entity.damage_stats.downtime = Some(get_downtime_stats(
+                    activity,
+                    fight_start,
+                    fight_end,
+                    &deaths,
+                    &forced_downtime,
+                    entity.damage_stats.damage_dealt,
+                ));
+            }
         }
 
         entity.damage_stats.dps = entity.damage_stats.damage_dealt / duration_seconds;
 
+        if let Some(log) = status_effect_log.get(&entity.name) {
+            entity.damage_stats.status_effect_uptime = log
+                .iter()
+                .map(|(buff_id, intervals)| (*buff_id, get_uptime(intervals, encounter.duration)))
+                .collect();
+
+            if entity.entity_type == EntityType::PLAYER {
+                let mut intervals = encounter
+                    .encounter_damage_stats
+                    .buffs
+                    .iter()
+                    .filter(|(_, se)| {
+                        se.buff_category == "identity"
+                            && se
+                                .source
+                                .skill
+                                .as_ref()
+                                .map_or(false, |skill| skill.class_id == entity.class_id)
+                    })
+                    .filter_map(|(id, _)| log.get(id))
+                    .flatten()
+                    .cloned()
+                    .collect::<Vec<_>>();
+                merge_intervals(&mut intervals);
+                entity.damage_stats.identity_uptime = get_uptime(&intervals, encounter.duration);
+            }
+        }
+
         if let Some(stats) = player_stats
             .as_ref()
             .and_then(|stats| stats.get(&entity.name))
@@ -913,18 +1229,33 @@ pub fn insert_data(
             }
         }
 
+        let swift = player_stats
+            .as_ref()
+            .and_then(|stats| stats.get(&entity.name))
+            .map(|stats| stats.stats.swift)
+            .unwrap_or_default();
+        for (_, skill) in entity.skills.iter_mut() {
+            skill.cooldown_stats = get_skill_cooldown_stats(skill, swift, encounter.duration);
+            // a single source that did all of the skill's damage adds nothing to the breakdown
+            if skill.sub_sources.len() == 1
+                && skill
+                    .sub_sources
+                    .values()
+                    .all(|sub_source| sub_source.damage == skill.total_damage)
+            {
+                skill.sub_sources.clear();
+            }
+        }
+
         for (_, skill_cast_log) in skill_cast_log.iter().filter(|&(s, _)| *s == entity.id) {
             for (skill, log) in skill_cast_log {
                 entity.skills.entry(*skill).and_modify(|e| {
-                    e.max_damage_cast = log
-                        .values()
-                        .map(|cast| cast.hits.iter().map(|hit| hit.damage).sum::<i64>())
-                        .max()
-                        .unwrap_or_default();
+                    // non-boss hits are kept so the log can switch damage views
                     e.skill_cast_log = log
                         .iter()
                         .map(|(_, skill_casts)| skill_casts.clone())
                         .collect();
+                    e.update_cast_stats(encounter.boss_only_damage);
                 });
             }
         }
@@ -934,24 +1265,45 @@ pub fn insert_data(
                 let mut total_identity_gain = 0;
                 let data = identity_log;
                 let duration_seconds = (data[data.len() - 1].0 - data[0].0) / 1000;
-                let max = match entity.class.as_str() {
-                    "Summoner" => 7_000.0,
-                    "Souleater" => 3_000.0,
-                    _ => 10_000.0,
+                let gauge = get_identity_gauge(entity.class_id);
+                let max = gauge.max as f32;
+
+                // bubbles are spent whole, so count them into the gauge
+                let gauge_value = |identity: &(u32, u32, u32)| {
+                    if gauge.kind == "bubbles" {
+                        identity.0 as f32 + max * identity.1 as f32
+                    } else {
+                        identity.0 as f32
+                    }
                 };
-                let stats: String = match entity.class.as_str() {
-                    "Arcanist" => {
+                entity.damage_stats.identity_spends = data
+                    .windows(2)
+                    .filter_map(|pair| {
+                        let (t2, curr) = pair[1];
+                        let spent = gauge_value(&pair[0].1) - gauge_value(&curr);
+                        if spent <= 0.0 {
+                            return None;
+                        }
+                        let relative_time = ((t2 - fight_start) as f32 / 1000.0) as i32;
+                        Some((relative_time, ((spent / max * 100.0) * 100.0).round() / 100.0))
+                    })
+                    .collect();
+
+                let stats: String = match gauge.kind.as_str() {
+                    "cards" => {
                         let mut cards: HashMap<u32, u32> = HashMap::new();
                         let mut log: Vec<(i32, (f32, u32, u32))> = Vec::new();
                         for i in 1..data.len() {
                             let (t1, prev) = data[i - 1];
                             let (t2, curr) = data[i];
 
-                            // don't count clown cards draws as card draws
-                            if curr.1 != 0 && curr.1 != prev.1 && prev.1 != 19284 {
+                            // e.g. clown card draws don't count as card draws
+                            let ignored = gauge.ignored_cards.contains(&prev.1);
+                            if curr.1 != 0 && curr.1 != prev.1 && !ignored {
                                 cards.entry(curr.1).and_modify(|e| *e += 1).or_insert(1);
                             }
-                            if curr.2 != 0 && curr.2 != prev.2 && prev.2 != 19284 {
+                            let ignored = gauge.ignored_cards.contains(&prev.2);
+                            if curr.2 != 0 && curr.2 != prev.2 && !ignored {
                                 cards.entry(curr.2).and_modify(|e| *e += 1).or_insert(1);
                             }
 
@@ -980,7 +1332,7 @@ pub fn insert_data(
 
                         serde_json::to_string(&identity_stats).unwrap()
                     }
-                    "Artist" | "Bard" => {
+                    "bubbles" => {
                         let mut log: Vec<(i32, (f32, u32))> = Vec::new();
 
                         for i in 1..data.len() {
@@ -996,13 +1348,13 @@ pub fn insert_data(
                             }
 
                             let relative_time = ((t2 - fight_start) as f32 / 1000.0) as i32;
-                            // since bard and artist have 3 bubbles, i.1 is the number of bubbles
-                            // we scale percentage to 3 bubbles
+                            // i.1 is the number of bubbles, scale percentage to all bubbles
                             // current bubble + max * number of bubbles
                             let percentage: f32 =
                                 ((((i2.0 as f32 + max * i2.1 as f32) / max) * 100.0) * 100.0)
                                     .round()
                                     / 100.0;
+                            let percentage = percentage.min(100.0 * gauge.bubbles.max(1) as f32);
                             log.push((relative_time, (percentage, i2.1)));
                         }
 
@@ -1085,10 +1437,18 @@ pub fn insert_data(
                 && e.damage_stats.damage_dealt > 0
         })
         .collect::<Vec<_>>();
-    let local_player_dps = players
+    // dps of the view the log was taken in, plus the boss only dps for the filter
+    let (local_player_dps, boss_only_local_player_dps) = players
         .iter()
         .find(|e| e.name == encounter.local_player)
-        .map(|e| e.damage_stats.dps)
+        .map(|e| {
+            let boss_damage = if encounter.boss_only_damage {
+                e.damage_stats.damage_dealt
+            } else {
+                e.damage_stats.damage_dealt - e.damage_stats.non_boss.damage
+            };
+            (e.damage_stats.dps, boss_damage / duration_seconds)
+        })
         .unwrap_or_default();
     players.sort_unstable_by_key(|e| Reverse(e.damage_stats.damage_dealt));
     let preview_players = players
@@ -1110,8 +1470,11 @@ pub fn insert_data(
         local_player,
         my_dps,
         cleared,
-        boss_only_damage
-    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        boss_only_damage,
+        boss_only_my_dps,
+        my_build,
+        my_party_supports
+    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
         )
         .expect("failed to prepare encounter preview statement");
     encounter_preview_stmt
@@ -1125,11 +1488,722 @@ pub fn insert_data(
             encounter.local_player,
             local_player_dps,
             raid_clear,
-            encounter.boss_only_damage
+            encounter.boss_only_damage,
+            boss_only_local_player_dps,
+            local_build,
+            local_party_supports
         ])
         .expect("failed to insert encounter preview");
 }
 
+fn get_skill_cooldown_stats(
+    skill: &Skill,
+    swift: u32,
+    duration: i64,
+) -> Option<SkillCooldownStats> {
+    if skill.cast_log.is_empty() || duration <= 0 {
+        return None;
+    }
+    let mut casts = skill
+        .cast_log
+        .iter()
+        .map(|cast| *cast as i64)
+        .collect::<Vec<_>>();
+    casts.sort_unstable();
+
+    let data_cooldown = SKILL_DATA
+        .get(&skill.id)
+        .and_then(|skill_data| skill_data.cooldown)
+        .filter(|cooldown| *cooldown > 0.0)
+        .map(|base| {
+            let mut cooldown = base as f64 * 1000.0;
+            for option in skill
+                .tripod_data
+                .iter()
+                .flatten()
+                .flat_map(|tripod| tripod.options.iter())
+                .filter(|option| option.effect_type == "change_cooldown")
+            {
+                let value = option.param.first().cloned().unwrap_or_default() as f64;
+                if option.param_type == "relative" {
+                    cooldown *= 1.0 + value / 100.0;
+                } else {
+                    cooldown += value;
+                }
+            }
+            // cooldown gems reduce by 2% per level
+            cooldown *= 1.0 - skill.gem_cooldown.unwrap_or_default() as f64 * 0.02;
+            cooldown *= 1.0 - swift as f64 * SWIFTNESS_COOLDOWN_RATE;
+            cooldown.round() as i64
+        });
+    // without skill data the shortest gap already includes every reduction
+    let shortest_gap = casts.windows(2).map(|w| w[1] - w[0]).min();
+    let (cooldown, source) = match (data_cooldown, shortest_gap) {
+        (Some(cooldown), _) => (cooldown, "data"),
+        (None, Some(gap)) if casts.len() >= OBSERVED_COOLDOWN_MIN_CASTS => (gap, "observed"),
+        _ => return None,
+    };
+    if cooldown <= 0 {
+        return None;
+    }
+
+    // skills are assumed to be off cooldown when the fight starts
+    let mut available_at = 0;
+    let mut total_delay = 0;
+    for cast in casts.iter() {
+        total_delay += (cast - available_at).max(0);
+        available_at = cast + cooldown;
+    }
+    let idle_time = total_delay + (duration - available_at).max(0);
+
+    Some(SkillCooldownStats {
+        cooldown,
+        source: source.to_string(),
+        expected_casts: duration / cooldown + 1,
+        casts: casts.len() as i64,
+        idle_time,
+        average_delay: total_delay as f64 / casts.len() as f64,
+    })
+}
+
+fn get_support_scorecards(
+    encounter: &Encounter,
+    party_info: &[Vec<String>],
+    caster_status_effect_log: &HashMap<String, HashMap<String, StatusEffectLog>>,
+    skill_cast_log: &HashMap<u64, HashMap<u32, BTreeMap<i64, SkillCast>>>,
+) -> HashMap<String, SupportScorecard> {
+    let mut scorecards: HashMap<String, SupportScorecard> = HashMap::new();
+    let players = encounter
+        .entities
+        .values()
+        .filter(|e| e.entity_type == EntityType::PLAYER && e.class_id != 0)
+        .collect::<Vec<_>>();
+    let bosses = encounter
+        .entities
+        .values()
+        .filter(|e| e.entity_type == EntityType::BOSS)
+        .map(|e| e.name.as_str())
+        .collect::<Vec<_>>();
+
+    for support in players.iter().filter(|e| is_support_class_id(e.class_id)) {
+        let (brands, ap_buffs, identity_buffs) = get_support_buff_ids(encounter, support.class_id);
+
+        // supports buff their own party, fall back to everyone if party info is missing
+        let party = party_info
+            .iter()
+            .find(|party| party.contains(&support.name))
+            .map(|party| {
+                players
+                    .iter()
+                    .filter(|e| party.contains(&e.name))
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_else(|| players.clone());
+        let members = party
+            .into_iter()
+            .filter(|e| e.name != support.name)
+            .collect::<Vec<_>>();
+
+        // only intervals this support cast, so two of the same class stay apart
+        let support_log = caster_status_effect_log.get(&support.name);
+        let uptime_of = |targets: &[&str], ids: &[u32]| {
+            let mut intervals = targets
+                .iter()
+                .filter_map(|target| support_log.and_then(|logs| logs.get(*target)))
+                .flat_map(|log| ids.iter().filter_map(|id| log.get(id)))
+                .flatten()
+                .cloned()
+                .collect::<Vec<_>>();
+            merge_intervals(&mut intervals);
+            get_uptime(&intervals, encounter.duration)
+        };
+        let party_uptime_of = |ids: &[u32]| {
+            if members.is_empty() {
+                return 0.0;
+            }
+            members
+                .iter()
+                .map(|e| uptime_of(&[e.name.as_str()], ids))
+                .sum::<f64>()
+                / members.len() as f64
+        };
+
+        let mut party_damage = 0;
+        let mut brand_damage = 0;
+        let mut ap_buff_damage = 0;
+        let mut identity_damage = 0;
+        let mut buff_damage: HashMap<u32, i64> = HashMap::new();
+        for member in members.iter() {
+            party_damage += member.damage_stats.damage_dealt;
+            for id in ap_buffs.iter().chain(identity_buffs.iter()) {
+                if let Some(damage) = member.damage_stats.buffed_by.get(id) {
+                    buff_damage
+                        .entry(*id)
+                        .and_modify(|e| *e += damage)
+                        .or_insert(*damage);
+                }
+            }
+            for id in brands.iter() {
+                if let Some(damage) = member.damage_stats.debuffed_by.get(id) {
+                    buff_damage
+                        .entry(*id)
+                        .and_modify(|e| *e += damage)
+                        .or_insert(*damage);
+                }
+            }
+
+            // per hit so a hit covered by several buffs of a category only counts once
+            let hits = skill_cast_log
+                .get(&member.id)
+                .into_iter()
+                .flat_map(|skills| skills.values())
+                .flat_map(|casts| casts.values())
+                .flat_map(|cast| cast.hits.iter())
+                .filter(|hit| hit.in_view(encounter.boss_only_damage));
+            for hit in hits {
+                if hit.debuffed_by.iter().any(|id| brands.contains(id)) {
+                    brand_damage += hit.damage;
+                }
+                if hit.buffed_by.iter().any(|id| ap_buffs.contains(id)) {
+                    ap_buff_damage += hit.damage;
+                }
+                if hit.buffed_by.iter().any(|id| identity_buffs.contains(id)) {
+                    identity_damage += hit.damage;
+                }
+            }
+        }
+        let share = |damage: i64| {
+            if party_damage > 0 {
+                damage as f64 / party_damage as f64
+            } else {
+                0.0
+            }
+        };
+
+        scorecards.insert(
+            support.name.clone(),
+            SupportScorecard {
+                class_id: support.class_id,
+                // brand on any boss counts, so earlier bosses aren't lost
+                brand_uptime: uptime_of(&bosses, &brands),
+                ap_buff_uptime: party_uptime_of(&ap_buffs),
+                identity_uptime: party_uptime_of(&identity_buffs),
+                shields_given: support.damage_stats.shields_given,
+                shields_absorbed: support.damage_stats.damage_absorbed_on_others,
+                brand_damage_share: share(brand_damage),
+                ap_buff_damage_share: share(ap_buff_damage),
+                identity_damage_share: share(identity_damage),
+                buff_damage_share: buff_damage
+                    .into_iter()
+                    .map(|(id, damage)| (id, share(damage)))
+                    .collect(),
+            },
+        );
+    }
+
+    scorecards
+}
+
+// brand, attack power and identity buff ids applied by the given support class
+fn get_support_buff_ids(encounter: &Encounter, class_id: u32) -> (Vec<u32>, Vec<u32>, Vec<u32>) {
+    // same criteria used for buffed_by_support/identity and debuffed_by_support
+    let from_support = |se: &StatusEffect| {
+        se.source
+            .skill
+            .as_ref()
+            .map_or(false, |skill| skill.class_id == class_id)
+            && se.buff_type & StatusEffectBuffTypeFlags::DMG.bits() != 0
+            && se.target == StatusEffectTarget::PARTY
+    };
+    let buffs = &encounter.encounter_damage_stats.buffs;
+    let debuffs = &encounter.encounter_damage_stats.debuffs;
+    let brands = debuffs
+        .iter()
+        .filter(|(_, se)| from_support(se))
+        .map(|(id, _)| *id)
+        .collect::<Vec<_>>();
+    let ap_buffs = buffs
+        .iter()
+        .filter(|(_, se)| from_support(se) && se.buff_category == "classskill")
+        .map(|(id, _)| *id)
+        .collect::<Vec<_>>();
+    let identity_buffs = buffs
+        .iter()
+        .filter(|(_, se)| from_support(se) && se.buff_category == "identity")
+        .map(|(id, _)| *id)
+        .collect::<Vec<_>>();
+
+    (brands, ap_buffs, identity_buffs)
+}
+
+fn get_burst_alignment(
+    encounter: &Encounter,
+    party_info: &[Vec<String>],
+    status_effect_log: &HashMap<String, StatusEffectLog>,
+    skill_cast_log: &HashMap<u64, HashMap<u32, BTreeMap<i64, SkillCast>>>,
+) -> (HashMap<i32, Vec<(i32, i32)>>, HashMap<String, BurstAlignment>) {
+    let mut burst_windows: HashMap<i32, Vec<(i32, i32)>> = HashMap::new();
+    let mut burst_alignment: HashMap<String, BurstAlignment> = HashMap::new();
+    let players = encounter
+        .entities
+        .values()
+        .filter(|e| e.entity_type == EntityType::PLAYER && e.class_id != 0)
+        .collect::<Vec<_>>();
+    // treat everyone as one party if party info is missing
+    let parties = if party_info.is_empty() {
+        vec![players.clone()]
+    } else {
+        party_info
+            .iter()
+            .map(|party| {
+                players
+                    .iter()
+                    .filter(|e| party.contains(&e.name))
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
+    let casts_of = |id: u64| {
+        skill_cast_log
+            .get(&id)
+            .into_iter()
+            .flat_map(|skills| skills.values())
+            .flat_map(|casts| casts.values())
+    };
+
+    for (index, party) in parties.iter().enumerate() {
+        let mut brands: Vec<u32> = Vec::new();
+        let mut ap_buffs: Vec<u32> = Vec::new();
+        let mut identity_buffs: Vec<u32> = Vec::new();
+        for support in party.iter().filter(|e| is_support_class_id(e.class_id)) {
+            let (b, a, i) = get_support_buff_ids(encounter, support.class_id);
+            brands.extend(b);
+            ap_buffs.extend(a);
+            identity_buffs.extend(i);
+        }
+        if brands.is_empty() || ap_buffs.is_empty() || identity_buffs.is_empty() {
+            continue;
+        }
+        let dps = party
+            .iter()
+            .filter(|e| !is_support_class_id(e.class_id))
+            .collect::<Vec<_>>();
+
+        // a window is any stretch where all three categories were up at once,
+        // brands are tracked on the bosses and buffs on the party's dps
+        let intervals_of = |targets: &[&str], ids: &[u32]| {
+            let mut intervals = targets
+                .iter()
+                .filter_map(|target| status_effect_log.get(*target))
+                .flat_map(|log| ids.iter().filter_map(|id| log.get(id)))
+                .flatten()
+                .cloned()
+                .collect::<Vec<_>>();
+            merge_intervals(&mut intervals);
+            intervals
+        };
+        let bosses = encounter
+            .entities
+            .values()
+            .filter(|e| e.entity_type == EntityType::BOSS)
+            .map(|e| e.name.as_str())
+            .collect::<Vec<_>>();
+        let members = dps.iter().map(|e| e.name.as_str()).collect::<Vec<_>>();
+        let windows = intersect_intervals(
+            &intersect_intervals(
+                &intervals_of(&bosses, &brands),
+                &intervals_of(&members, &ap_buffs),
+            ),
+            &intervals_of(&members, &identity_buffs),
+        );
+        if windows.is_empty() {
+            continue;
+        }
+        let in_window = |timestamp: i64| {
+            windows
+                .iter()
+                .any(|(start, end)| timestamp >= *start as i64 && timestamp <= *end as i64)
+        };
+
+        for player in dps.iter() {
+            let casts = casts_of(player.id)
+                .map(|cast| {
+                    let damage = cast
+                        .hits
+                        .iter()
+                        .filter(|hit| hit.in_view(encounter.boss_only_damage))
+                        .map(|hit| hit.damage)
+                        .sum::<i64>();
+                    (cast, damage)
+                })
+                .filter(|(_, damage)| *damage > 0)
+                .collect::<Vec<_>>();
+            if casts.is_empty() {
+                continue;
+            }
+
+            // big skills are the top quarter of the player's casts by damage
+            let mut damages = casts.iter().map(|(_, damage)| *damage).collect::<Vec<_>>();
+            damages.sort_unstable();
+            let threshold = damages[damages.len() * 3 / 4];
+
+            let mut alignment = BurstAlignment {
+                party: index as i32,
+                ..Default::default()
+            };
+            for (cast, damage) in casts.iter().filter(|(_, damage)| *damage >= threshold) {
+                let aligned_damage = cast
+                    .hits
+                    .iter()
+                    .filter(|hit| hit.in_view(encounter.boss_only_damage))
+                    .filter(|hit| in_window(hit.timestamp))
+                    .map(|hit| hit.damage)
+                    .sum::<i64>();
+                alignment.big_casts += 1;
+                alignment.big_cast_damage += damage;
+                alignment.aligned_damage += aligned_damage;
+                if aligned_damage > 0 {
+                    alignment.aligned_casts += 1;
+                }
+            }
+            alignment.score = alignment.aligned_damage as f64 / alignment.big_cast_damage as f64;
+            burst_alignment.insert(player.name.clone(), alignment);
+        }
+
+        burst_windows.insert(index as i32, windows);
+    }
+
+    (burst_windows, burst_alignment)
+}
+
+// class engravings that put a support class on a dps build
+fn is_dps_support_engraving(engraving_id: u32) -> bool {
+    matches!(engraving_id, 282 | 194 | 305) // judgement, true courage, recurrence
+}
+
+fn get_player_roles(
+    encounter: &Encounter,
+    player_stats: &Option<Cache<String, PlayerStats>>,
+    support_scorecards: &HashMap<String, SupportScorecard>,
+) -> HashMap<String, PlayerRole> {
+    encounter
+        .entities
+        .values()
+        .filter(|e| e.entity_type == EntityType::PLAYER && e.class_id != 0)
+        .map(|player| {
+            // highest level class engraving decides the build
+            let class_engraving = player_stats
+                .as_ref()
+                .and_then(|stats| stats.get(&player.name))
+                .and_then(|stats| get_engravings(player.class_id, &stats.engravings))
+                .and_then(|engravings| engravings.class_engravings)
+                .and_then(|engravings| engravings.into_iter().next());
+            let support = if !is_support_class_id(player.class_id) {
+                false
+            } else if let Some(engraving) = class_engraving.as_ref() {
+                !is_dps_support_engraving(engraving.id)
+            } else {
+                // no engravings, a support that never buffed their party is playing dps
+                support_scorecards.get(&player.name).map_or(true, |s| {
+                    s.brand_uptime > 0.0 || s.ap_buff_uptime > 0.0 || s.identity_uptime > 0.0
+                })
+            };
+            let role = PlayerRole {
+                role: if support { "support" } else { "dps" }.to_string(),
+                build: class_engraving
+                    .map(|e| e.name)
+                    .or_else(|| get_identity_build(player)),
+            };
+            (player.name.clone(), role)
+        })
+        .collect()
+}
+
+// without engravings, builds are only known when one of them locks out a stance,
+// named after the class engraving so they match builds read from engravings.
+// only deadeye and gunslinger builds can be told apart this way, engravings are
+// never sent in local packets so every other build needs the stats api
+fn get_identity_build(player: &EncounterEntity) -> Option<String> {
+    let used_stance = |stance: &str| {
+        player.skills.values().any(|skill| {
+            skill.total_damage > 0
+                && SKILL_DATA
+                    .get(&skill.id)
+                    .and_then(|skill| skill.identity_category.as_deref())
+                    == Some(stance)
+        })
+    };
+    let build = match player.class_id {
+        // pistoleer is locked to the handgun stance
+        503 if used_stance("devil_hunter_shotgun") || used_stance("devil_hunter_rifle") => {
+            "Enhanced Weapon"
+        }
+        503 if used_stance("devil_hunter_pistol") => "Pistoleer",
+        // time to hunt has no shotgun stance
+        512 if used_stance("devil_hunter_shotgun") => "Peacemaker",
+        512 if used_stance("devil_hunter_pistol") || used_stance("devil_hunter_rifle") => {
+            "Time to Hunt"
+        }
+        _ => return None,
+    };
+
+    Some(build.to_string())
+}
+
+fn get_party_composition(
+    party_info: &[Vec<String>],
+    player_roles: &HashMap<String, PlayerRole>,
+) -> HashMap<i32, PartyComposition> {
+    // treat everyone as one party if party info is missing
+    let parties = if party_info.is_empty() {
+        vec![player_roles.keys().cloned().collect::<Vec<_>>()]
+    } else {
+        party_info.to_vec()
+    };
+
+    parties
+        .iter()
+        .enumerate()
+        .filter_map(|(index, party)| {
+            let mut composition = PartyComposition::default();
+            for role in party.iter().filter_map(|name| player_roles.get(name)) {
+                if role.role == "support" {
+                    composition.supports += 1;
+                } else {
+                    composition.dps += 1;
+                }
+                if let Some(build) = role.build.as_ref() {
+                    composition.builds.push(build.clone());
+                }
+            }
+            if composition.supports + composition.dps == 0 {
+                return None;
+            }
+            composition.builds.sort();
+            Some((index as i32, composition))
+        })
+        .collect()
+}
+
+// gaps longer than DOWNTIME_GAP_MS between hits, relative to fight start
+fn get_activity_gaps(activity: &[i64], fight_start: i64, fight_end: i64) -> Vec<(i64, i64)> {
+    let duration = fight_end - fight_start;
+    let mut gaps: Vec<(i64, i64)> = Vec::new();
+    let mut prev = 0;
+    for timestamp in activity {
+        let timestamp = timestamp - fight_start;
+        if timestamp - prev > DOWNTIME_GAP_MS {
+            gaps.push((prev, timestamp));
+        }
+        prev = max(prev, timestamp);
+    }
+    if duration - prev > DOWNTIME_GAP_MS {
+        gaps.push((prev, duration));
+    }
+    gaps
+}
+
+// time nobody could deal damage, the boss was invincible or the whole party stopped hitting
+fn get_forced_downtime(
+    activity_log: &HashMap<String, Vec<i64>>,
+    fight_start: i64,
+    fight_end: i64,
+    boss_invincible_log: &[(i32, i32)],
+) -> Vec<(i32, i32)> {
+    let mut party_activity: Vec<i64> = activity_log.values().flatten().copied().collect();
+    party_activity.sort_unstable();
+    let mut forced_downtime: Vec<(i32, i32)> =
+        get_activity_gaps(&party_activity, fight_start, fight_end)
+            .into_iter()
+            .map(|(start, end)| (start as i32, end as i32))
+            .chain(boss_invincible_log.iter().copied())
+            .collect();
+    merge_intervals(&mut forced_downtime);
+    forced_downtime
+}
+
+fn get_downtime_stats(
+    activity: &[i64],
+    fight_start: i64,
+    fight_end: i64,
+    deaths: &[i64],
+    forced_downtime: &[(i32, i32)],
+    damage_dealt: i64,
+) -> DowntimeStats {
+    let duration = fight_end - fight_start;
+    let gaps = get_activity_gaps(activity, fight_start, fight_end);
+
+    let mut stats = DowntimeStats::default();
+    for (start, end) in gaps {
+        let length = end - start;
+        let invulnerable: i64 = forced_downtime
+            .iter()
+            .map(|(a, b)| (end.min(*b as i64) - start.max(*a as i64)).max(0))
+            .sum();
+        let reason = if deaths.iter().any(|death| *death >= start && *death <= end) {
+            stats.dead_time += length;
+            "dead"
+        } else if invulnerable * 2 >= length {
+            stats.invulnerable_time += length;
+            "invulnerable"
+        } else {
+            stats.other_time += length;
+            "other"
+        };
+        stats.gaps.push(DowntimeGap {
+            start,
+            end,
+            reason: reason.to_string(),
+        });
+    }
+
+    stats.active_time =
+        max(duration - stats.dead_time - stats.invulnerable_time - stats.other_time, 0);
+    if duration > 0 {
+        stats.active_time_percent = stats.active_time as f64 / duration as f64;
+    }
+    stats.active_dps = damage_dealt / max(stats.active_time / 1000, 1);
+
+    stats
+}
+
+fn get_awakening_summary(encounter: &Encounter) -> HashMap<String, AwakeningSummary> {
+    let mut summaries: HashMap<String, AwakeningSummary> = HashMap::new();
+    for entity in encounter.entities.values() {
+        let log = &entity.damage_stats.awakening_log;
+        if entity.entity_type != EntityType::PLAYER || log.is_empty() {
+            continue;
+        }
+
+        let summary = AwakeningSummary {
+            awakening_casts: log.len() as i64,
+            hyper_awakening_casts: log.iter().filter(|a| a.hyper_awakening).count() as i64,
+            awakening_damage: log.iter().map(|a| a.damage).sum(),
+            average_boss_hp_percent: log.iter().map(|a| a.boss_hp_percent).sum::<f32>()
+                / log.len() as f32,
+        };
+        summaries.insert(entity.name.clone(), summary);
+    }
+
+    summaries
+}
+
+fn get_utility_stats(encounter: &Encounter) -> HashMap<String, UtilityStats> {
+    let mut utility_stats: HashMap<String, UtilityStats> = HashMap::new();
+    for entity in encounter.entities.values() {
+        if entity.entity_type != EntityType::PLAYER {
+            continue;
+        }
+
+        let damage_stats = &entity.damage_stats;
+        let counters = damage_stats
+            .counter_log
+            .iter()
+            .filter(|counter| counter.success)
+            .count() as i64;
+        let mut stats = UtilityStats {
+            counters,
+            failed_counters: damage_stats.counter_log.len() as i64 - counters,
+            ..Default::default()
+        };
+        for skill in entity.skills.values() {
+            let skill_data = match SKILL_DATA.get(&skill.id) {
+                Some(skill_data) => skill_data,
+                None => continue,
+            };
+            // parts and weak points only exist on the boss
+            let boss_hits = if encounter.boss_only_damage {
+                skill.hits
+            } else {
+                skill.hits - skill.non_boss.hits
+            };
+            if boss_hits <= 0 {
+                continue;
+            }
+            if skill_data.destruction.unwrap_or(0) > 0 {
+                stats.destruction_hits += boss_hits;
+                stats.destruction_by_skill.insert(skill.id, boss_hits);
+            }
+            if skill_data.weak_point.unwrap_or(0) > 0 {
+                stats.weak_point_hits += boss_hits;
+                stats.weak_point_by_skill.insert(skill.id, boss_hits);
+            }
+        }
+        if stats.counters > 0
+            || stats.failed_counters > 0
+            || stats.destruction_hits > 0
+            || stats.weak_point_hits > 0
+        {
+            utility_stats.insert(entity.name.clone(), stats);
+        }
+    }
+
+    utility_stats
+}
+
+// average of how close each projection was, relative to how far ahead it was made
+pub fn get_kill_projection_accuracy(log: &[(i32, i32)], kill_time: i32) -> Option<f64> {
+    let scores: Vec<f64> = log
+        .iter()
+        .filter(|(projected_at, _)| *projected_at < kill_time)
+        .map(|(projected_at, projected_kill)| {
+            let error = (projected_kill - kill_time).abs() as f64;
+            (1.0 - error / (kill_time - projected_at) as f64).max(0.0)
+        })
+        .collect();
+    if scores.is_empty() {
+        None
+    } else {
+        Some(scores.iter().sum::<f64>() / scores.len() as f64)
+    }
+}
+
+// sorts and merges overlapping intervals in place
+pub fn merge_intervals(intervals: &mut Vec<(i32, i32)>) {
+    intervals.sort_unstable_by_key(|(start, _)| *start);
+    let mut merged: Vec<(i32, i32)> = Vec::with_capacity(intervals.len());
+    for &(start, end) in intervals.iter() {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    *intervals = merged;
+}
+
+// expects merged intervals
+pub fn intersect_intervals(a: &[(i32, i32)], b: &[(i32, i32)]) -> Vec<(i32, i32)> {
+    let mut intersection: Vec<(i32, i32)> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        let start = a[i].0.max(b[j].0);
+        let end = a[i].1.min(b[j].1);
+        if start < end {
+            intersection.push((start, end));
+        }
+        if a[i].1 < b[j].1 {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    intersection
+}
+
+// expects merged intervals
+pub fn get_uptime(intervals: &[(i32, i32)], duration: i64) -> f64 {
+    if duration <= 0 {
+        return 0.0;
+    }
+    let active: i64 = intervals
+        .iter()
+        .map(|(start, end)| (end - start) as i64)
+        .sum();
+    (active as f64 / duration as f64).min(1.0)
+}
+
 pub fn map_status_effect(se: &StatusEffectDetails, custom_id_map: &mut HashMap<u32, u32>) -> u32 {
     if se.custom_id > 0 {
         custom_id_map.insert(se.custom_id, se.status_effect_id);
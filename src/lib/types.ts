@@ -67,6 +67,13 @@ export interface EncounterMisc {
     partyInfo?: PartyInfo;
     rdpsValid?: boolean;
     rdpsMessage?: string;
+    rdpsInvalidation?: RdpsInvalidation;
+    rdpsLocal?: boolean;
+}
+
+export interface RdpsInvalidation {
+    reasons: Array<string>;
+    missingStats: { [key: string]: number };
 }
 
 export interface PartyInfo {
@@ -197,9 +204,18 @@ export interface DamageStats {
     rdpsDamageReceived: number;
     rdpsDamageReceivedSupport: number;
     rdpsDamageGiven: number;
+    nonBoss?: NonBossDamage;
     [key: string]: any;
 }
 
+// damage to targets other than the boss, lets a log switch between damage views
+export interface NonBossDamage {
+    damage: number;
+    hits: number;
+    crits: number;
+    critDamage: number;
+}
+
 export interface SkillStats {
     casts: number;
     hits: number;
@@ -448,6 +464,8 @@ export class SearchFilter {
     cleared: boolean;
     difficulty: string;
     bossOnlyDamage: boolean;
+    build: string;
+    partySupports: number | null;
     sort: string;
     order: number;
 
@@ -459,6 +477,8 @@ export class SearchFilter {
         this.cleared = false;
         this.difficulty = "";
         this.bossOnlyDamage = false;
+        this.build = "";
+        this.partySupports = null;
         this.sort = "id";
         this.order = 2;
     }
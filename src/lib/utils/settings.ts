@@ -9,6 +9,7 @@ import { clickthroughStore } from "$lib/utils/stores";
 export const defaultSettings = {
     general: {
         lowPerformanceMode: false,
+        localRdps: false,
         showNames: true,
         showGearScore: false,
         hideNames: false,